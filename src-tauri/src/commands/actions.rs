@@ -0,0 +1,216 @@
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use crate::commands::{docker_commands, process_analyzer, shell_commands};
+use crate::monitor::audit_log::AuditLog;
+use crate::monitor::docker::{DockerContainer, DockerMonitor};
+pub use crate::monitor::model::{ActionKind, RiskLevel};
+use crate::monitor::process_detail::{self, AccountCache};
+use crate::monitor::process_sampler::ProcessSampler;
+use crate::monitor::process_terminator::TerminateOptions;
+use crate::rules::protection_policy::ProtectionPolicyStore;
+
+// 查看容器日志时返回的行数
+const CONTAINER_LOG_LINES: usize = 200;
+// 查看进程日志时每个文件返回的行数
+const PROCESS_LOG_LINES: usize = 200;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActionOutcome {
+    pub kind: ActionKind,
+    /// 提示性建议和手动操作为false
+    pub executed: bool,
+    pub message: String,
+    /// 操作返回的数据，如终止结果、重启结果、容器信息或日志
+    pub data: Option<serde_json::Value>,
+}
+
+impl ActionOutcome {
+    fn done(kind: ActionKind, message: String) -> Self {
+        ActionOutcome {
+            kind,
+            executed: true,
+            message,
+            data: None,
+        }
+    }
+
+    fn skipped(kind: ActionKind, message: &str) -> Self {
+        ActionOutcome {
+            kind,
+            executed: false,
+            message: message.to_string(),
+            data: None,
+        }
+    }
+
+    fn with_data<T: Serialize>(mut self, data: &T) -> Result<Self, String> {
+        self.data = Some(serde_json::to_value(data).map_err(|e| e.to_string())?);
+        Ok(self)
+    }
+}
+
+/// 执行端口诊断或文件占用分析给出的操作建议
+///
+/// 进程相关操作与对应的独立命令一样经过保护策略检查并记录审计日志，
+/// 需要确认的操作先通过 `check_process_protection` 获取确认令牌。
+#[tauri::command]
+pub async fn execute_action(
+    kind: ActionKind,
+    confirm_token: Option<String>,
+    sampler: State<'_, ProcessSampler>,
+    policy: State<'_, ProtectionPolicyStore>,
+    audit: State<'_, AuditLog>,
+    accounts: State<'_, AccountCache>,
+) -> Result<ActionOutcome, String> {
+    match &kind {
+        ActionKind::StopProcess { pid } => {
+            let pid = *pid;
+            let result = process_analyzer::terminate_process(pid, None, confirm_token, sampler, policy, audit).await?;
+            ActionOutcome::done(kind, format!("已终止进程 {}", pid)).with_data(&result)
+        }
+        ActionKind::ForceKillProcess { pid } => {
+            let pid = *pid;
+            let options = TerminateOptions {
+                force: true,
+                ..TerminateOptions::default()
+            };
+            let result = process_analyzer::terminate_process(pid, Some(options), confirm_token, sampler, policy, audit).await?;
+            ActionOutcome::done(kind, format!("已强制终止进程 {}", pid)).with_data(&result)
+        }
+        ActionKind::KillProcessTree { pid } => {
+            let pid = *pid;
+            let result = process_analyzer::kill_process_tree(pid, confirm_token, sampler, policy, audit).await?;
+            ActionOutcome::done(kind, format!("已终止进程 {} 及其 {} 个子进程", pid, result.targets.len().saturating_sub(1)))
+                .with_data(&result)
+        }
+        ActionKind::RestartProcess { pid } => {
+            let pid = *pid;
+            let result = process_analyzer::restart_process(pid, None, confirm_token, sampler, policy, audit).await?;
+            ActionOutcome::done(kind, format!("进程已重启，新PID为 {}", result.new_pid)).with_data(&result)
+        }
+        ActionKind::ShowProcessDetail { pid } => {
+            let pid = *pid;
+            let detail = process_analyzer::get_process_detail(pid, sampler, policy, accounts)
+                .await?
+                .ok_or_else(|| format!("进程 {} 不存在", pid))?;
+            ActionOutcome::done(kind, format!("进程 {} 的详细信息", pid)).with_data(&detail)
+        }
+        ActionKind::ShowProcessLogs { pid } => {
+            let pid = *pid;
+            let logs = process_detail::read_process_logs(pid, PROCESS_LOG_LINES)
+                .ok_or_else(|| format!("无法读取进程 {} 打开的文件，可能权限不足或当前系统不支持", pid))?;
+            let message = if logs.is_empty() {
+                format!("进程 {} 没有打开日志文件", pid)
+            } else {
+                format!("进程 {} 的 {} 个日志文件最近 {} 行", pid, logs.len(), PROCESS_LOG_LINES)
+            };
+            ActionOutcome::done(kind, message).with_data(&logs)
+        }
+        ActionKind::StopContainer { container_id } => {
+            docker_commands::stop_container(container_id, audit).await?;
+            Ok(ActionOutcome::done(kind.clone(), format!("已停止容器 {}", container_id)))
+        }
+        ActionKind::RestartContainer { container_id } => {
+            docker_commands::restart_container(container_id, audit).await?;
+            Ok(ActionOutcome::done(kind.clone(), format!("已重启容器 {}", container_id)))
+        }
+        ActionKind::ShowContainer { container_id } => {
+            let container = find_container(container_id)?;
+            ActionOutcome::done(kind.clone(), format!("容器 {} 的详细信息", container.name)).with_data(&container)
+        }
+        ActionKind::ShowContainerLogs { container_id } => {
+            let logs = DockerMonitor::new().get_container_logs(container_id, CONTAINER_LOG_LINES)?;
+            ActionOutcome::done(kind.clone(), format!("容器 {} 最近 {} 行日志", container_id, CONTAINER_LOG_LINES))
+                .with_data(&logs)
+        }
+        ActionKind::StopContainerOnPort { port } => {
+            let container = find_container_on_port(*port)?;
+            docker_commands::stop_container(&container.id, audit).await?;
+            ActionOutcome::done(kind.clone(), format!("已停止容器 {}", container.name)).with_data(&container)
+        }
+        ActionKind::ShowContainerOnPort { port } => {
+            let container = find_container_on_port(*port)?;
+            ActionOutcome::done(kind.clone(), format!("容器 {} 的详细信息", container.name)).with_data(&container)
+        }
+        ActionKind::OpenBrowser { url } => {
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                return Err(format!("不支持的地址: {}", url));
+            }
+
+            shell_commands::open_with_default_app(url)?;
+            Ok(ActionOutcome::done(kind.clone(), format!("已在浏览器中打开 {}", url)))
+        }
+        ActionKind::OpenFolder { path } => {
+            let folder = folder_to_open(Path::new(path))?;
+            let folder = folder.to_string_lossy();
+
+            shell_commands::open_with_default_app(&folder)?;
+            Ok(ActionOutcome::done(kind.clone(), format!("已打开 {}", folder)))
+        }
+        ActionKind::OpenTaskManager => {
+            shell_commands::open_task_manager().await?;
+            Ok(ActionOutcome::done(kind, "已打开任务管理器".to_string()))
+        }
+        ActionKind::UseAlternativePort { .. } | ActionKind::WaitForRelease { .. } => {
+            Ok(ActionOutcome::skipped(kind, "该建议仅供参考，无需执行操作"))
+        }
+        ActionKind::Manual => Ok(ActionOutcome::skipped(kind, "该操作需要手动完成")),
+    }
+}
+
+// 默认程序打开可执行文件时会直接运行它，因此只打开目录，文件改为打开其所在目录
+fn folder_to_open(path: &Path) -> Result<PathBuf, String> {
+    if path.is_dir() {
+        return Ok(path.to_path_buf());
+    }
+
+    match path.parent() {
+        Some(parent) if path.exists() && parent.is_dir() => Ok(parent.to_path_buf()),
+        _ => Err(format!("路径 {} 不存在", path.display())),
+    }
+}
+
+// 支持完整ID、短ID前缀或容器名
+fn find_container(container_id: &str) -> Result<DockerContainer, String> {
+    DockerMonitor::new()
+        .get_containers()?
+        .into_iter()
+        .find(|c| c.id.starts_with(container_id) || c.name == container_id)
+        .ok_or_else(|| format!("容器 {} 不存在", container_id))
+}
+
+fn find_container_on_port(port: u16) -> Result<DockerContainer, String> {
+    DockerMonitor::new()
+        .get_container_by_port(port)?
+        .ok_or_else(|| format!("没有映射到端口 {} 的容器", port))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn opens_folder_instead_of_file() {
+        let dir = std::env::temp_dir().join(format!("actions_folder_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("server.exe");
+        fs::write(&file, "").unwrap();
+
+        assert_eq!(folder_to_open(&dir).unwrap(), dir);
+        assert_eq!(folder_to_open(&file).unwrap(), dir);
+        assert!(folder_to_open(&dir.join("missing")).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn action_kinds_match_frontend_format() {
+        let kind: ActionKind = serde_json::from_str(r#"{"ShowProcessLogs":{"pid":42}}"#).unwrap();
+        assert_eq!(kind, ActionKind::ShowProcessLogs { pid: 42 });
+
+        let kind: ActionKind = serde_json::from_str(r#""OpenTaskManager""#).unwrap();
+        assert_eq!(kind, ActionKind::OpenTaskManager);
+    }
+}
@@ -0,0 +1,10 @@
+use tauri::State;
+use crate::monitor::audit_log::{AuditLog, AuditQuery, AuditRecord};
+
+#[tauri::command]
+pub async fn get_audit_log(
+    query: Option<AuditQuery>,
+    audit: State<'_, AuditLog>,
+) -> Result<Vec<AuditRecord>, String> {
+    Ok(audit.query(&query.unwrap_or_default()))
+}
@@ -1,3 +1,5 @@
+use tauri::State;
+use crate::monitor::audit_log::{AuditLog, AuditRecord};
 use crate::monitor::docker::{DockerMonitor, DockerContainer};
 
 #[tauri::command]
@@ -13,15 +15,21 @@ pub async fn get_container_by_port(port: u16) -> Result<Option<DockerContainer>,
 }
 
 #[tauri::command]
-pub async fn stop_container(container_id: &str) -> Result<(), String> {
+pub async fn stop_container(container_id: &str, audit: State<'_, AuditLog>) -> Result<(), String> {
     let docker_monitor = DockerMonitor::new();
-    docker_monitor.stop_container(container_id)
+    let result = docker_monitor.stop_container(container_id);
+
+    audit.record(AuditRecord::new("stop_container").container(container_id).finish(&result));
+    result
 }
 
 #[tauri::command]
-pub async fn restart_container(container_id: &str) -> Result<(), String> {
+pub async fn restart_container(container_id: &str, audit: State<'_, AuditLog>) -> Result<(), String> {
     let docker_monitor = DockerMonitor::new();
-    docker_monitor.restart_container(container_id)
+    let result = docker_monitor.restart_container(container_id);
+
+    audit.record(AuditRecord::new("restart_container").container(container_id).finish(&result));
+    result
 }
 
 #[tauri::command]
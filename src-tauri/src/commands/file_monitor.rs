@@ -1,237 +1,29 @@
-use serde::{Deserialize, Serialize};
-use std::path::Path;
-// 移除未使用的导入
+use tauri::{AppHandle, Manager, State};
+use crate::monitor::file::FileMonitor;
+use crate::monitor::file_lock;
+pub use crate::monitor::model::FileOccupancy;
+use crate::monitor::process_sampler::ProcessSampler;
+use crate::rules::protection_policy::ProtectionPolicyStore;
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct FileOccupancy {
-    pub file_path: String,
-    pub file_name: String,
-    pub occupied_by: Vec<ProcessInfo>,
-    pub reason: String,
-    pub impact: String,
-    pub solutions: Vec<Solution>,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct ProcessInfo {
-    pub pid: u32,
-    pub name: String,
-    pub friendly_name: String,
-    pub exe_path: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Solution {
-    pub action: String,
-    pub description: String,
-    pub risk_level: String,
-    pub command: Option<String>,
+/// 当前平台是否支持文件占用检测，不支持时前端不提供检查入口
+#[tauri::command]
+pub async fn is_file_monitor_supported() -> bool {
+    file_lock::is_supported()
 }
 
+/// 检查文件或目录的占用情况，目录会检查其下的全部文件
 #[tauri::command]
-pub async fn check_file_occupancy(file_path: String) -> Result<Option<FileOccupancy>, String> {
-    // 检查文件是否存在
-    if !Path::new(&file_path).exists() {
-        return Err(format!("文件 {} 不存在", file_path));
-    }
-    
-    // 在实际项目中，我们需要使用特殊的API来检查文件占用
-    // 这里是一个简单的模拟实现
-    
-    // 模拟检测文件占用
-    detect_file_occupancy(&file_path)
-}
+pub async fn check_file_occupancy(
+    file_path: String,
+    app_handle: AppHandle,
+    policy: State<'_, ProtectionPolicyStore>,
+) -> Result<Option<FileOccupancy>, String> {
+    let monitor = FileMonitor::new().with_policy(policy.policy());
 
-// 这个函数在实际项目中需要使用Windows API实现
-fn detect_file_occupancy(file_path: &str) -> Result<Option<FileOccupancy>, String> {
-    // 这里只是一个模拟实现，实际项目中需要使用Windows API
-    // 例如，可以使用RestartManager API或其他方法
-    
-    // 模拟一些常见的文件占用情况
-    let extension = Path::new(file_path)
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("");
-        
-    // 根据文件扩展名模拟不同的占用情况
-    match extension {
-        "pdf" => {
-            // 模拟PDF文件被Adobe Reader占用
-            let process = ProcessInfo {
-                pid: 9999, // 模拟PID
-                name: "AcroRd32.exe".to_string(),
-                friendly_name: "Adobe Acrobat Reader".to_string(),
-                exe_path: Some("C:\\Program Files (x86)\\Adobe\\Acrobat Reader DC\\Reader\\AcroRd32.exe".to_string()),
-            };
-            
-            let file_name = Path::new(file_path)
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown")
-                .to_string();
-                
-            let processes = vec![process.clone()];
-            let (reason, impact, solutions) = generate_reason_and_solutions(file_path, &processes);
-            
-            Ok(Some(FileOccupancy {
-                file_path: file_path.to_string(),
-                file_name,
-                occupied_by: processes,
-                reason,
-                impact,
-                solutions,
-            }))
-        },
-        "docx" | "doc" => {
-            // 模拟Word文档被Office占用
-            let process = ProcessInfo {
-                pid: 9998, // 模拟PID
-                name: "WINWORD.EXE".to_string(),
-                friendly_name: "Microsoft Word".to_string(),
-                exe_path: Some("C:\\Program Files\\Microsoft Office\\root\\Office16\\WINWORD.EXE".to_string()),
-            };
-            
-            let file_name = Path::new(file_path)
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown")
-                .to_string();
-                
-            let processes = vec![process.clone()];
-            let (reason, impact, solutions) = generate_reason_and_solutions(file_path, &processes);
-            
-            Ok(Some(FileOccupancy {
-                file_path: file_path.to_string(),
-                file_name,
-                occupied_by: processes,
-                reason,
-                impact,
-                solutions,
-            }))
-        },
-        "xlsx" | "xls" => {
-            // 模拟Excel文件被Office占用
-            let process = ProcessInfo {
-                pid: 9997, // 模拟PID
-                name: "EXCEL.EXE".to_string(),
-                friendly_name: "Microsoft Excel".to_string(),
-                exe_path: Some("C:\\Program Files\\Microsoft Office\\root\\Office16\\EXCEL.EXE".to_string()),
-            };
-            
-            let file_name = Path::new(file_path)
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown")
-                .to_string();
-                
-            let processes = vec![process.clone()];
-            let (reason, impact, solutions) = generate_reason_and_solutions(file_path, &processes);
-            
-            Ok(Some(FileOccupancy {
-                file_path: file_path.to_string(),
-                file_name,
-                occupied_by: processes,
-                reason,
-                impact,
-                solutions,
-            }))
-        },
-        _ => Ok(None), // 其他类型文件暂不模拟占用
-    }
+    // 需要遍历全部进程的文件描述符和内存映射，放到独立线程中执行
+    tokio::task::spawn_blocking(move || {
+        monitor.check_file_occupancy(&file_path, &app_handle.state::<ProcessSampler>())
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
-
-fn generate_reason_and_solutions(_file_path: &str, processes: &[ProcessInfo]) -> (String, String, Vec<Solution>) {
-    if processes.is_empty() {
-        return (
-            "未知原因".to_string(),
-            "Low".to_string(),
-            vec![],
-        );
-    }
-    
-    let process = &processes[0];
-    
-    // 根据进程类型生成不同的原因和解决方案
-    match process.name.as_str() {
-        "AcroRd32.exe" => {
-            (
-                "文件正在被PDF阅读器查看，无法删除或移动".to_string(),
-                "Medium".to_string(),
-                vec![
-                    Solution {
-                        action: "关闭PDF阅读器".to_string(),
-                        description: "关闭Adobe Acrobat Reader后再进行文件操作".to_string(),
-                        risk_level: "低".to_string(),
-                        command: None,
-                    },
-                    Solution {
-                        action: "强制关闭".to_string(),
-                        description: "强制终止Adobe Reader进程，可能导致未保存的更改丢失".to_string(),
-                        risk_level: "中".to_string(),
-                        command: Some(format!("taskkill /F /PID {}", process.pid)),
-                    },
-                ],
-            )
-        },
-        "WINWORD.EXE" => {
-            (
-                "文件正在被Microsoft Word编辑，无法删除或移动".to_string(),
-                "High".to_string(),
-                vec![
-                    Solution {
-                        action: "保存并关闭Word".to_string(),
-                        description: "在Word中保存文档并关闭后再进行文件操作".to_string(),
-                        risk_level: "低".to_string(),
-                        command: None,
-                    },
-                    Solution {
-                        action: "强制关闭".to_string(),
-                        description: "强制终止Word进程，可能导致未保存的更改丢失".to_string(),
-                        risk_level: "高".to_string(),
-                        command: Some(format!("taskkill /F /PID {}", process.pid)),
-                    },
-                ],
-            )
-        },
-        "EXCEL.EXE" => {
-            (
-                "文件正在被Microsoft Excel编辑，无法删除或移动".to_string(),
-                "High".to_string(),
-                vec![
-                    Solution {
-                        action: "保存并关闭Excel".to_string(),
-                        description: "在Excel中保存文档并关闭后再进行文件操作".to_string(),
-                        risk_level: "低".to_string(),
-                        command: None,
-                    },
-                    Solution {
-                        action: "强制关闭".to_string(),
-                        description: "强制终止Excel进程，可能导致未保存的更改丢失".to_string(),
-                        risk_level: "高".to_string(),
-                        command: Some(format!("taskkill /F /PID {}", process.pid)),
-                    },
-                ],
-            )
-        },
-        _ => {
-            (
-                format!("文件正在被{}使用，无法删除或移动", process.friendly_name),
-                "Medium".to_string(),
-                vec![
-                    Solution {
-                        action: "关闭应用".to_string(),
-                        description: format!("关闭{}后再进行文件操作", process.friendly_name),
-                        risk_level: "低".to_string(),
-                        command: None,
-                    },
-                    Solution {
-                        action: "强制关闭".to_string(),
-                        description: format!("强制终止{}进程，可能导致未保存的更改丢失", process.friendly_name),
-                        risk_level: "中".to_string(),
-                        command: Some(format!("taskkill /F /PID {}", process.pid)),
-                    },
-                ],
-            )
-        },
-    }
-}
\ No newline at end of file
@@ -1,6 +1,11 @@
 pub mod port_monitor;
+pub mod port_allocator;
+pub mod port_watcher;
+pub mod port_history;
 pub mod process_analyzer;
 pub mod file_monitor;
 pub mod docker_commands;
 pub mod system_info;
-pub mod shell_commands;
\ No newline at end of file
+pub mod shell_commands;
+pub mod audit_log;
+pub mod actions;
\ No newline at end of file
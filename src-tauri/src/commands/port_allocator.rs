@@ -0,0 +1,416 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::State;
+use crate::commands::port_monitor::{PortInfo, ProcessInfo, ProjectInfo};
+use crate::rules::process_rules::ProcessRules;
+use crate::rules::project_detector;
+use crate::utils::port_utils;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PortReservation {
+    pub port: u16,
+    pub protocol: String,
+    pub project: String,
+    pub path: Option<String>,
+    pub note: Option<String>,
+    pub reserved_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FreePortQuery {
+    pub preferred: Option<u16>,
+    pub range_start: u16,
+    pub range_end: u16,
+    pub protocol: String,
+    pub count: usize,
+    pub avoid_well_known: bool,
+    // 为该项目预留的端口视为可用
+    pub project: Option<String>,
+}
+
+impl Default for FreePortQuery {
+    fn default() -> Self {
+        FreePortQuery {
+            preferred: None,
+            range_start: 1024,
+            range_end: 65535,
+            protocol: "TCP".to_string(),
+            count: 1,
+            avoid_well_known: true,
+            project: None,
+        }
+    }
+}
+
+/// 端口预留记录，保存在应用数据目录下的JSON文件中
+pub struct PortReservationStore {
+    path: PathBuf,
+    reservations: Mutex<Vec<PortReservation>>,
+}
+
+impl PortReservationStore {
+    pub fn load(path: PathBuf) -> Self {
+        // 文件不存在或内容损坏时从空列表开始
+        let reservations = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        PortReservationStore {
+            path,
+            reservations: Mutex::new(reservations),
+        }
+    }
+
+    pub fn list(&self) -> Vec<PortReservation> {
+        self.reservations.lock().unwrap().clone()
+    }
+
+    pub fn reserve(&self, reservation: PortReservation) -> Result<(), String> {
+        let mut reservations = self.reservations.lock().unwrap();
+
+        reservations.retain(|r| !(r.port == reservation.port && r.protocol == reservation.protocol));
+        reservations.push(reservation);
+        reservations.sort_by_key(|r| r.port);
+
+        self.save(&reservations)
+    }
+
+    pub fn release(&self, port: u16, protocol: &str) -> Result<bool, String> {
+        let mut reservations = self.reservations.lock().unwrap();
+        let before = reservations.len();
+
+        reservations.retain(|r| !(r.port == port && r.protocol == protocol));
+
+        if reservations.len() == before {
+            return Ok(false);
+        }
+
+        self.save(&reservations)?;
+        Ok(true)
+    }
+
+    fn save(&self, reservations: &[PortReservation]) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("创建数据目录失败: {}", e))?;
+        }
+
+        let content = serde_json::to_string_pretty(reservations)
+            .map_err(|e| format!("序列化端口预留失败: {}", e))?;
+
+        fs::write(&self.path, content).map_err(|e| format!("保存端口预留失败: {}", e))
+    }
+}
+
+#[tauri::command]
+pub async fn find_free_ports(
+    query: FreePortQuery,
+    store: State<'_, PortReservationStore>,
+) -> Result<Vec<u16>, String> {
+    if query.range_start > query.range_end {
+        return Err("端口范围无效".to_string());
+    }
+
+    let reserved: HashSet<(u16, String)> = store
+        .list()
+        .into_iter()
+        .filter(|r| query.project.as_deref() != Some(r.project.as_str()))
+        .map(|r| (r.port, r.protocol))
+        .collect();
+
+    // 需要逐个绑定候选端口，范围较大时耗时较长，放到独立线程中执行
+    tokio::task::spawn_blocking(move || search_free_ports(&query, &reserved))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn reserve_port(
+    port: u16,
+    protocol: Option<String>,
+    project: String,
+    path: Option<String>,
+    note: Option<String>,
+    store: State<'_, PortReservationStore>,
+) -> Result<PortReservation, String> {
+    if port == 0 {
+        return Err("端口号无效".to_string());
+    }
+
+    let reservation = PortReservation {
+        port,
+        protocol: normalize_protocol(protocol),
+        project,
+        path,
+        note,
+        reserved_at: chrono::Local::now().timestamp(),
+    };
+
+    store.reserve(reservation.clone())?;
+    Ok(reservation)
+}
+
+#[tauri::command]
+pub async fn release_port(
+    port: u16,
+    protocol: Option<String>,
+    store: State<'_, PortReservationStore>,
+) -> Result<bool, String> {
+    store.release(port, &normalize_protocol(protocol))
+}
+
+#[tauri::command]
+pub async fn get_port_reservations(
+    store: State<'_, PortReservationStore>,
+) -> Result<Vec<PortReservation>, String> {
+    Ok(store.list())
+}
+
+/// 为端口列表标注预留信息，被预留项目以外的进程占用时标记为冲突
+pub fn apply_reservations(ports: &mut [PortInfo], reservations: &[PortReservation]) {
+    for port in ports.iter_mut() {
+        let reservation = reservations.iter().find(|r| {
+            r.port == port.port && (r.protocol == "BOTH" || r.protocol == port.protocol)
+        });
+
+        if let Some(reservation) = reservation {
+            // 所属进程未知或读取不到命令行时无法判断是否为预留的项目，不算冲突
+            let owner_known = !port.process.is_unknown() && (!port.process.cmd.is_empty() || port.project.is_some());
+            port.reservation_conflict =
+                owner_known && !reservation_matches(reservation, &port.process, &port.project);
+            port.reservation = Some(reservation.clone());
+        }
+    }
+}
+
+fn reservation_matches(
+    reservation: &PortReservation,
+    process: &ProcessInfo,
+    project: &Option<ProjectInfo>,
+) -> bool {
+    // 指定了项目路径时以路径为准
+    if let Some(path) = &reservation.path {
+        let project_path_matches = project
+            .as_ref()
+            .and_then(|p| p.path.as_ref())
+            .map_or(false, |p| p.starts_with(path.as_str()));
+
+        return project_path_matches || process.cmd.iter().any(|arg| arg.contains(path.as_str()));
+    }
+
+    project.as_ref().map_or(false, |p| p.name == reservation.project)
+        || process.cmd.iter().any(|arg| arg.contains(&reservation.project))
+}
+
+fn normalize_protocol(protocol: Option<String>) -> String {
+    match protocol.map(|p| p.to_uppercase()).as_deref() {
+        Some("UDP") => "UDP".to_string(),
+        Some("BOTH") => "BOTH".to_string(),
+        _ => "TCP".to_string(),
+    }
+}
+
+// 规则库中各端口范围的起始端口即为常见框架的默认端口，例如3000、5173、8080
+fn well_known_ports() -> HashSet<u16> {
+    let process_ranges = ProcessRules::new()
+        .rules
+        .into_iter()
+        .filter_map(|rule| rule.port_ranges);
+
+    let project_ranges = project_detector::get_project_signatures()
+        .into_iter()
+        .filter_map(|signature| signature.port_ranges);
+
+    process_ranges
+        .chain(project_ranges)
+        .flatten()
+        .map(|(start, _)| start)
+        .collect()
+}
+
+// 预留的协议与查询的协议有重叠时才视为不可用，例如UDP的预留不影响查找TCP端口
+fn is_reserved(reserved: &HashSet<(u16, String)>, port: u16, protocol: &str) -> bool {
+    reserved.iter().any(|(reserved_port, reserved_protocol)| {
+        *reserved_port == port
+            && (reserved_protocol == "BOTH" || protocol == "BOTH" || reserved_protocol == protocol)
+    })
+}
+
+fn search_free_ports(query: &FreePortQuery, reserved: &HashSet<(u16, String)>) -> Vec<u16> {
+    search_ports_with(query, reserved, port_utils::is_port_free)
+}
+
+fn search_ports_with(
+    query: &FreePortQuery,
+    reserved: &HashSet<(u16, String)>,
+    is_free: impl Fn(u16, &str) -> bool,
+) -> Vec<u16> {
+    let protocol = normalize_protocol(Some(query.protocol.clone()));
+    let start = query.range_start.max(1);
+    let end = query.range_end.max(start);
+    let center = query.preferred.unwrap_or(start).clamp(start, end);
+    let avoided = if query.avoid_well_known {
+        well_known_ports()
+    } else {
+        HashSet::new()
+    };
+
+    let mut found = Vec::new();
+    let max_distance = (center - start).max(end - center);
+
+    // 从首选端口开始向两侧扩展，保证结果按距离排序
+    for distance in 0..=max_distance {
+        let candidates = if distance == 0 {
+            [Some(center), None]
+        } else {
+            [
+                center.checked_add(distance).filter(|p| *p <= end),
+                center.checked_sub(distance).filter(|p| *p >= start),
+            ]
+        };
+
+        for candidate in candidates.into_iter().flatten() {
+            // 用户明确指定的首选端口即使是框架默认端口也允许使用
+            if Some(candidate) != query.preferred && avoided.contains(&candidate) {
+                continue;
+            }
+
+            if is_reserved(reserved, candidate, &protocol) || !is_free(candidate, &protocol) {
+                continue;
+            }
+
+            found.push(candidate);
+            if found.len() >= query.count.max(1) {
+                return found;
+            }
+        }
+    }
+
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(preferred: u16, range: (u16, u16), count: usize, avoid_well_known: bool) -> FreePortQuery {
+        FreePortQuery {
+            preferred: Some(preferred),
+            range_start: range.0,
+            range_end: range.1,
+            count,
+            avoid_well_known,
+            ..FreePortQuery::default()
+        }
+    }
+
+    fn reservation(port: u16, protocol: &str, project: &str) -> PortReservation {
+        PortReservation {
+            port,
+            protocol: protocol.to_string(),
+            project: project.to_string(),
+            path: None,
+            note: None,
+            reserved_at: 0,
+        }
+    }
+
+    fn listening(port: u16, cmd: &[&str]) -> PortInfo {
+        PortInfo {
+            port,
+            local_address: "0.0.0.0".to_string(),
+            protocol: "TCP".to_string(),
+            status: "LISTENING".to_string(),
+            process: ProcessInfo {
+                pid: 100,
+                name: "node".to_string(),
+                exe_path: None,
+                cmd: cmd.iter().map(|arg| arg.to_string()).collect(),
+                parent_pid: None,
+            },
+            owners: Vec::new(),
+            project: None,
+            suggestions: Vec::new(),
+            reservation: None,
+            reservation_conflict: false,
+            health: None,
+            fingerprint: None,
+            backend: "procfs".to_string(),
+        }
+    }
+
+    #[test]
+    fn finds_nearest_ports_first() {
+        let busy = [5000, 5002];
+        let found = search_ports_with(&query(5000, (4990, 5010), 4, false), &HashSet::new(), |port, _| {
+            !busy.contains(&port)
+        });
+
+        assert_eq!(found, vec![5001, 4999, 4998, 5003]);
+    }
+
+    #[test]
+    fn skips_framework_defaults_unless_preferred() {
+        assert!(well_known_ports().contains(&3000));
+
+        let found = search_ports_with(&query(2999, (2990, 3010), 3, true), &HashSet::new(), |_, _| true);
+        assert_eq!(found, vec![2999, 2998, 3001]);
+
+        let found = search_ports_with(&query(3000, (2990, 3010), 1, true), &HashSet::new(), |_, _| true);
+        assert_eq!(found, vec![3000]);
+    }
+
+    #[test]
+    fn reservations_only_hide_overlapping_protocols() {
+        let reserved: HashSet<(u16, String)> = [(5353, "UDP".to_string())].into_iter().collect();
+        let mut tcp = query(5353, (5350, 5360), 1, false);
+        tcp.protocol = "tcp".to_string();
+        let mut udp = query(5353, (5350, 5360), 1, false);
+        udp.protocol = "UDP".to_string();
+
+        assert_eq!(search_ports_with(&tcp, &reserved, |_, _| true), vec![5353]);
+        assert_eq!(search_ports_with(&udp, &reserved, |_, _| true), vec![5354]);
+
+        let both: HashSet<(u16, String)> = [(5353, "BOTH".to_string())].into_iter().collect();
+        assert_eq!(search_ports_with(&tcp, &both, |_, _| true), vec![5354]);
+    }
+
+    #[test]
+    fn flags_reserved_port_taken_by_another_project() {
+        let reservations = vec![reservation(3000, "TCP", "shop-frontend"), reservation(4000, "UDP", "admin")];
+        let mut ports = vec![
+            listening(3000, &["node", "/srv/blog/server.js"]),
+            listening(4000, &["node", "/srv/blog/server.js"]),
+            listening(5000, &["node"]),
+        ];
+        let mut own = listening(3000, &["node", "/srv/shop-frontend/vite.js"]);
+
+        apply_reservations(&mut ports, &reservations);
+        apply_reservations(std::slice::from_mut(&mut own), &reservations);
+
+        assert!(ports[0].reservation_conflict);
+        assert_eq!(ports[0].reservation.as_ref().unwrap().project, "shop-frontend");
+        // UDP预留不影响TCP端口
+        assert!(ports[1].reservation.is_none());
+        assert!(ports[2].reservation.is_none() && !ports[2].reservation_conflict);
+        assert!(own.reservation.is_some() && !own.reservation_conflict);
+    }
+
+    #[test]
+    fn unknown_owner_is_not_a_conflict() {
+        let reservations = vec![reservation(3000, "TCP", "shop-frontend")];
+        let mut unknown = listening(3000, &[]);
+        unknown.process.pid = 0;
+        let mut unreadable = listening(3000, &[]);
+
+        apply_reservations(std::slice::from_mut(&mut unknown), &reservations);
+        apply_reservations(std::slice::from_mut(&mut unreadable), &reservations);
+
+        assert!(unknown.reservation.is_some() && !unknown.reservation_conflict);
+        assert!(unreadable.reservation.is_some() && !unreadable.reservation_conflict);
+    }
+}
@@ -0,0 +1,41 @@
+use tauri::State;
+use crate::monitor::port_history::{
+    PortHistoryQuery, PortHistoryRecord, PortHistoryRetention, PortHistoryStore, MAX_RETENTION_HOURS,
+};
+
+#[tauri::command]
+pub async fn get_port_history(
+    query: PortHistoryQuery,
+    store: State<'_, PortHistoryStore>,
+) -> Result<Vec<PortHistoryRecord>, String> {
+    Ok(store.query(&query))
+}
+
+#[tauri::command]
+pub async fn get_port_history_retention(
+    store: State<'_, PortHistoryStore>,
+) -> Result<PortHistoryRetention, String> {
+    Ok(store.retention())
+}
+
+#[tauri::command]
+pub async fn set_port_history_retention(
+    retention: PortHistoryRetention,
+    store: State<'_, PortHistoryStore>,
+) -> Result<(), String> {
+    if retention.max_records == 0 {
+        return Err("保留条数必须大于0".to_string());
+    }
+
+    // 为0时会立即删除全部历史
+    if retention.max_age_hours == 0 || retention.max_age_hours > MAX_RETENTION_HOURS {
+        return Err(format!("保留时长必须在1到{}小时之间", MAX_RETENTION_HOURS));
+    }
+
+    store.set_retention(retention)
+}
+
+#[tauri::command]
+pub async fn clear_port_history(store: State<'_, PortHistoryStore>) -> Result<(), String> {
+    store.clear()
+}
@@ -1,18 +1,36 @@
 ﻿use serde::{Deserialize, Serialize};
-use sysinfo::{System, Pid, ProcessRefreshKind, RefreshKind};
-use std::process::Command;
+use std::collections::HashMap;
+use tauri::State;
+use crate::commands::actions::{ActionKind, RiskLevel};
+use crate::commands::port_allocator::{self, PortReservation, PortReservationStore};
+use crate::monitor::docker::{DockerContainer, DockerMonitor};
+use crate::monitor::fingerprint::{self, ProtocolFingerprint};
+use crate::monitor::health::{self, HealthStatus};
+use crate::monitor::port::{self, PortMonitor};
+pub use crate::monitor::model::ActionSuggestion;
+use crate::monitor::process_sampler::ProcessSampler;
 use crate::rules::project_detector;
-use crate::rules::process_rules::ProcessRules;
-use crate::utils::process_utils;
+use crate::rules::protection_policy::{ProtectionPolicy, ProtectionPolicyStore};
+use crate::utils::port_utils;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PortInfo {
     pub port: u16,
+    pub local_address: String,
     pub protocol: String,
     pub status: String,
+    /// 主进程，多个进程共享套接字时为其他持有者的父进程
     pub process: ProcessInfo,
+    /// 持有该套接字的全部进程（含主进程），主进程排在最前
+    pub owners: Vec<ProcessInfo>,
     pub project: Option<ProjectInfo>,
     pub suggestions: Vec<ActionSuggestion>,
+    pub reservation: Option<PortReservation>,
+    pub reservation_conflict: bool,
+    pub health: Option<HealthStatus>,
+    pub fingerprint: Option<ProtocolFingerprint>,
+    /// 采集该端口数据的后端（procfs、ss、netstat）
+    pub backend: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -21,9 +39,27 @@ pub struct ProcessInfo {
     pub name: String,
     pub exe_path: Option<String>,
     pub cmd: Vec<String>,
+    pub parent_pid: Option<u32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl ProcessInfo {
+    /// 无权限查看套接字所属进程时的占位，PID为0
+    pub fn unknown() -> Self {
+        ProcessInfo {
+            pid: 0,
+            name: "未知进程".to_string(),
+            exe_path: None,
+            cmd: Vec::new(),
+            parent_pid: None,
+        }
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        self.pid == 0
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ProjectInfo {
     pub name: String,
     pub project_type: String,
@@ -32,194 +68,375 @@ pub struct ProjectInfo {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct ActionSuggestion {
-    pub action: String,
-    pub description: String,
-    pub risk_level: String,
+pub struct ConnectionInfo {
+    pub protocol: String,
+    pub local_address: String,
+    pub local_port: u16,
+    pub remote_address: String,
+    pub remote_port: u16,
+    pub state: String,
+    pub process: Option<ProcessInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProcessConnectionStats {
+    pub pid: u32,
+    pub name: String,
+    pub total: usize,
+    pub states: HashMap<String, usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConnectionList {
+    pub connections: Vec<ConnectionInfo>,
+    pub state_counts: HashMap<String, usize>,
+    pub process_stats: Vec<ProcessConnectionStats>,
+    pub backend: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PortOwner {
+    pub process: ProcessInfo,
+    pub project: Option<ProjectInfo>,
+    pub addresses: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PortDiagnosis {
+    pub port: u16,
+    pub in_use: bool,
+    pub summary: String,
+    pub sockets: Vec<ConnectionInfo>,
+    pub dual_stack: bool,
+    pub owners: Vec<PortOwner>,
+    pub container: Option<DockerContainer>,
+    pub suggestions: Vec<ActionSuggestion>,
+    pub suggested_port: Option<u16>,
 }
 
 #[tauri::command]
-pub async fn get_port_info(port: u16) -> Result<Option<PortInfo>, String> {
-    let ports = get_listening_ports().map_err(|e| e.to_string())?;
+pub async fn get_port_info(
+    port: u16,
+    reservations: State<'_, PortReservationStore>,
+    policy: State<'_, ProtectionPolicyStore>,
+    sampler: State<'_, ProcessSampler>,
+) -> Result<Option<PortInfo>, String> {
+    let mut ports = get_listening_ports(&sampler, policy.policy())?;
+    port_allocator::apply_reservations(&mut ports, &reservations.list());
     Ok(ports.into_iter().find(|p| p.port == port))
 }
 
 #[tauri::command]
-pub async fn get_all_ports() -> Result<Vec<PortInfo>, String> {
-    get_listening_ports().map_err(|e| e.to_string())
-}
-
-fn get_listening_ports() -> Result<Vec<PortInfo>, Box<dyn std::error::Error>> {
-    let mut ports = Vec::new();
-    let mut system = System::new_with_specifics(
-        RefreshKind::new().with_processes(ProcessRefreshKind::everything())
-    );
-    system.refresh_processes();
-
-    // 使用netstat获取端口信息
-    let output = if cfg!(target_os = "windows") {
-        Command::new("netstat")
-            .args(["-ano", "-p", "TCP"])
-            .output()?
-    } else {
-        Command::new("netstat")
-            .args(["-tlnp"])
-            .output()?
-    };
+pub async fn get_all_ports(
+    probe: Option<bool>,
+    fingerprint: Option<bool>,
+    reservations: State<'_, PortReservationStore>,
+    policy: State<'_, ProtectionPolicyStore>,
+    sampler: State<'_, ProcessSampler>,
+) -> Result<Vec<PortInfo>, String> {
+    let mut ports = get_listening_ports(&sampler, policy.policy())?;
+    port_allocator::apply_reservations(&mut ports, &reservations.list());
 
-    let output_str = String::from_utf8_lossy(&output.stdout);
-    
-    // 加载进程规则
-    let process_rules = ProcessRules::new();
-    
-    for line in output_str.lines() {
-        if let Some(port_info) = parse_netstat_line(line, &system, &process_rules) {
-            ports.push(port_info);
-        }
+    // 健康检查和协议识别需要逐个连接端口，由调用方按需开启；连接会阻塞等待，放到独立线程中执行
+    let probe = probe.unwrap_or(false);
+    let fingerprint = fingerprint.unwrap_or(false);
+    if probe || fingerprint {
+        ports = tokio::task::spawn_blocking(move || {
+            if probe {
+                health::probe_ports(&mut ports);
+            }
+
+            if fingerprint {
+                fingerprint::fingerprint_ports(&mut ports);
+            }
+
+            ports
+        })
+        .await
+        .map_err(|e| e.to_string())?;
     }
 
     Ok(ports)
 }
 
-fn parse_netstat_line(line: &str, system: &System, process_rules: &ProcessRules) -> Option<PortInfo> {
-    let parts: Vec<&str> = line.split_whitespace().collect();
-    
-    if parts.len() < 4 {
-        return None;
-    }
+#[tauri::command]
+pub async fn get_connections(sampler: State<'_, ProcessSampler>) -> Result<ConnectionList, String> {
+    let mut monitor = PortMonitor::new();
+    let sockets = monitor.sockets().map_err(|e| e.to_string())?;
+    let pids: Vec<u32> = sockets.iter().flat_map(|socket| socket.pids.iter().copied()).collect();
 
-    // Windows netstat格式: TCP 0.0.0.0:3000 0.0.0.0:0 LISTENING 1234
-    if cfg!(target_os = "windows") && parts.len() >= 5 {
-        if parts[0] != "TCP" || parts[3] != "LISTENING" {
-            return None;
-        }
+    let connections = port::resolve_connections(&sampler.snapshot_including(&pids), sockets);
+    let backend = monitor.backend_name().unwrap_or_default();
 
-        let local_addr = parts[1];
-        let port = extract_port_from_addr(local_addr)?;
-        let pid: u32 = parts[4].parse().ok()?;
+    Ok(summarize_connections(connections, backend))
+}
 
-        let process_info = get_process_info_by_pid(pid, system)?;
-        
-        // 使用智能识别引擎检测项目信息
-        let project_info = project_detector::detect_project(&process_info, port);
-        
-        // 生成操作建议
-        let suggestions = generate_suggestions(&process_info, &project_info, process_rules);
+#[tauri::command]
+pub async fn diagnose_port(
+    port: u16,
+    policy: State<'_, ProtectionPolicyStore>,
+) -> Result<PortDiagnosis, String> {
+    let policy = policy.policy();
 
-        return Some(PortInfo {
-            port,
-            protocol: "TCP".to_string(),
-            status: "LISTENING".to_string(),
-            process: process_info,
-            project: project_info,
-            suggestions,
-        });
-    }
+    // 查询Docker需要启动子进程，查找替代端口需要逐个绑定，放到独立线程中执行
+    tokio::task::spawn_blocking(move || {
+        let mut monitor = PortMonitor::new().with_policy(policy);
+        let connections = monitor.get_connections().map_err(|e| e.to_string())?;
+        let sockets: Vec<ConnectionInfo> = connections
+            .into_iter()
+            .filter(|c| c.local_port == port)
+            .collect();
 
-    None
-}
+        // Docker不可用时不影响诊断结果
+        let container = DockerMonitor::new().get_container_by_port(port).ok().flatten();
 
-fn extract_port_from_addr(addr: &str) -> Option<u16> {
-    addr.split(':').last()?.parse().ok()
+        Ok(build_port_diagnosis(&monitor, port, sockets, container))
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
-fn get_process_info_by_pid(pid: u32, system: &System) -> Option<ProcessInfo> {
-    let process = system.process(Pid::from(pid as usize))?;
-    
-    Some(ProcessInfo {
-        pid,
-        name: process.name().to_string(),
-        exe_path: process.exe().map(|path| path.to_string_lossy().to_string()),
-        cmd: process.cmd().to_vec(),
-    })
+/// 使用采样器的进程快照解析端口所属进程，避免每次扫描都重建进程表
+///
+/// 套接字在锁定快照之前采集，扫描期间不阻塞采样线程。
+pub fn get_listening_ports(sampler: &ProcessSampler, policy: ProtectionPolicy) -> Result<Vec<PortInfo>, String> {
+    let mut monitor = PortMonitor::new().with_policy(policy);
+    let sockets = monitor.listening_sockets().map_err(|e| e.to_string())?;
+    let pids: Vec<u32> = sockets.iter().flat_map(|socket| socket.pids.iter().copied()).collect();
+
+    let system = sampler.snapshot_including(&pids);
+    Ok(monitor.resolve_listening_ports(&system, sockets))
 }
 
-fn generate_suggestions(
-    process: &ProcessInfo, 
-    project: &Option<ProjectInfo>, 
-    process_rules: &ProcessRules
-) -> Vec<ActionSuggestion> {
-    let mut suggestions = Vec::new();
+fn summarize_connections(connections: Vec<ConnectionInfo>, backend: &str) -> ConnectionList {
+    let mut state_counts: HashMap<String, usize> = HashMap::new();
+    let mut process_stats: HashMap<u32, ProcessConnectionStats> = HashMap::new();
 
-    // 首先检查是否有预定义的规则
-    if let Some(rule) = process_rules.get_rule_by_name(&process.name) {
-        for action in &rule.actions {
-            let (description, risk_level) = match action.as_str() {
-                "停止服务" => ("终止进程释放端口", "低"),
-                "重启" => ("重启服务", "低"),
-                "查看日志" => ("查看应用日志", "无"),
-                "关闭" => ("关闭应用", "低"),
-                "查看容器" => ("显示相关Docker容器信息", "无"),
-                "停止容器" => ("停止Docker容器释放端口", "中"),
-                "查看任务管理器" => ("查看浏览器任务管理器", "无"),
-                "保存文档" => ("保存当前文档后关闭", "低"),
-                "查看服务详情" => ("查看Windows服务详情", "无"),
-                _ => ("执行操作", "中"),
-            };
-            
-            suggestions.push(ActionSuggestion {
-                action: action.clone(),
-                description: description.to_string(),
-                risk_level: risk_level.to_string(),
+    for connection in &connections {
+        *state_counts.entry(connection.state.clone()).or_insert(0) += 1;
+
+        if let Some(process) = &connection.process {
+            let stats = process_stats.entry(process.pid).or_insert_with(|| ProcessConnectionStats {
+                pid: process.pid,
+                name: process.name.clone(),
+                total: 0,
+                states: HashMap::new(),
             });
+
+            stats.total += 1;
+            *stats.states.entry(connection.state.clone()).or_insert(0) += 1;
         }
-        
-        return suggestions;
     }
 
-    // 如果没有预定义规则，使用通用逻辑
-    match process.name.as_str() {
-        "node.exe" | "node" => {
-            suggestions.push(ActionSuggestion {
-                action: "停止服务".to_string(),
-                description: "终止Node.js进程释放端口".to_string(),
-                risk_level: "低".to_string(),
-            });
-            
-            if project.is_some() {
-                suggestions.push(ActionSuggestion {
-                    action: "重启服务".to_string(),
-                    description: "重启开发服务器".to_string(),
-                    risk_level: "低".to_string(),
-                });
-                
-                suggestions.push(ActionSuggestion {
-                    action: "打开浏览器".to_string(),
-                    description: "在浏览器中查看应用".to_string(),
-                    risk_level: "无".to_string(),
-                });
-            }
+    // 连接数最多的进程排在前面
+    let mut process_stats: Vec<ProcessConnectionStats> = process_stats.into_values().collect();
+    process_stats.sort_by(|a, b| b.total.cmp(&a.total).then(a.pid.cmp(&b.pid)));
+
+    ConnectionList {
+        connections,
+        state_counts,
+        process_stats,
+        backend: backend.to_string(),
+    }
+}
+
+// 按冲突套接字的协议查找替代端口，只有容器映射而没有套接字时按TCP处理
+fn conflict_protocol(sockets: &[ConnectionInfo]) -> &'static str {
+    let has_tcp = sockets.iter().any(|s| s.protocol.eq_ignore_ascii_case("TCP"));
+    let has_udp = sockets.iter().any(|s| s.protocol.eq_ignore_ascii_case("UDP"));
+
+    match (has_tcp, has_udp) {
+        (true, true) => "BOTH",
+        (false, true) => "UDP",
+        _ => "TCP",
+    }
+}
+
+fn build_port_diagnosis(
+    monitor: &PortMonitor,
+    port: u16,
+    sockets: Vec<ConnectionInfo>,
+    container: Option<DockerContainer>,
+) -> PortDiagnosis {
+    let listeners: Vec<&ConnectionInfo> = sockets
+        .iter()
+        .filter(|s| s.state == "LISTENING")
+        .collect();
+
+    // 按进程归并监听套接字，记录每个进程绑定的地址
+    let mut processes: Vec<ProcessInfo> = Vec::new();
+    let mut addresses: HashMap<u32, Vec<String>> = HashMap::new();
+    for socket in &listeners {
+        let process = match &socket.process {
+            Some(process) => process,
+            None => continue,
+        };
+
+        let address = format!("{}/{}", socket.local_address, socket.protocol);
+        let entry = addresses.entry(process.pid).or_default();
+        if !entry.contains(&address) {
+            entry.push(address);
         }
-        "docker-proxy.exe" | "docker-proxy" => {
-            suggestions.push(ActionSuggestion {
-                action: "查看容器".to_string(),
-                description: "显示相关Docker容器信息".to_string(),
-                risk_level: "无".to_string(),
-            });
-            
-            suggestions.push(ActionSuggestion {
-                action: "停止容器".to_string(),
-                description: "停止Docker容器释放端口".to_string(),
-                risk_level: "中".to_string(),
-            });
+        if !processes.iter().any(|p| p.pid == process.pid) {
+            processes.push(process.clone());
+        }
+    }
+
+    // 各工作进程分别持有套接字时补充共同的主进程，主进程排在最前
+    let processes = monitor.complete_owners(processes);
+    let owners: Vec<PortOwner> = processes
+        .iter()
+        .map(|process| PortOwner {
+            process: process.clone(),
+            project: project_detector::detect_project(process, port),
+            addresses: addresses.remove(&process.pid).unwrap_or_default(),
+        })
+        .collect();
+
+    let has_ipv4 = listeners.iter().any(|s| !s.local_address.contains(':'));
+    let has_ipv6 = listeners.iter().any(|s| s.local_address.contains(':'));
+    let dual_stack = has_ipv4 && has_ipv6;
+    let time_wait_count = sockets.iter().filter(|s| s.state == "TIME_WAIT").count();
+    let in_use = !listeners.is_empty() || time_wait_count > 0 || container.is_some();
+
+    let summary = if let Some(owner) = owners.first() {
+        match &owner.project {
+            Some(project) => format!(
+                "端口 {} 被 {} (PID {}) 占用，识别为{}",
+                port, owner.process.name, owner.process.pid, project.name
+            ),
+            None => format!("端口 {} 被 {} (PID {}) 占用", port, owner.process.name, owner.process.pid),
+        }
+    } else if let Some(container) = &container {
+        format!("端口 {} 被Docker容器 {} 映射", port, container.name)
+    } else if !listeners.is_empty() {
+        format!("端口 {} 正在被监听，但无权限查看所属进程", port)
+    } else if time_wait_count > 0 {
+        format!("端口 {} 上还有 {} 个TIME_WAIT连接，通常会在一分钟内自动释放", port, time_wait_count)
+    } else {
+        format!("端口 {} 当前空闲", port)
+    };
+
+    let mut suggestions = Vec::new();
+
+    if let Some(container) = &container {
+        suggestions.push(ActionSuggestion::new(
+            "停止容器",
+            format!("停止Docker容器 {} 释放端口", container.name),
+            RiskLevel::Medium,
+            ActionKind::StopContainer { container_id: container.id.clone() },
+        ));
+    }
+
+    // 只为主进程生成终止建议，单独结束工作进程只会被主进程重新拉起
+    let pids: Vec<u32> = processes.iter().map(|p| p.pid).collect();
+    for owner in &owners {
+        if owner.process.parent_pid.map_or(false, |ppid| pids.contains(&ppid)) {
+            continue;
         }
-        _ => {
-            // 检查是否是系统进程
-            if process_utils::is_system_process(&process.name) {
-                suggestions.push(ActionSuggestion {
-                    action: "查看详情".to_string(),
-                    description: "查看系统进程详细信息".to_string(),
-                    risk_level: "无".to_string(),
-                });
-            } else {
-                suggestions.push(ActionSuggestion {
-                    action: "终止进程".to_string(),
-                    description: "强制终止进程释放端口".to_string(),
-                    risk_level: "高".to_string(),
-                });
+
+        for suggestion in monitor.suggestions(&owner.process, &processes, &owner.project, port) {
+            if !suggestions.iter().any(|s: &ActionSuggestion| s.kind == suggestion.kind) {
+                suggestions.push(suggestion);
             }
         }
     }
 
-    suggestions
+    if listeners.is_empty() && time_wait_count > 0 {
+        suggestions.push(ActionSuggestion::new(
+            "等待释放",
+            "等待TIME_WAIT连接超时，或在服务中启用SO_REUSEADDR".to_string(),
+            RiskLevel::None,
+            ActionKind::WaitForRelease { port },
+        ));
+    }
+
+    let suggested_port = if in_use {
+        port_utils::find_nearest_free_port(port, conflict_protocol(&sockets), 100)
+    } else {
+        None
+    };
+
+    if let Some(free_port) = suggested_port {
+        suggestions.push(ActionSuggestion::new(
+            "更换端口",
+            format!("改用最近的空闲端口 {}", free_port),
+            RiskLevel::None,
+            ActionKind::UseAlternativePort { port: free_port },
+        ));
+    }
+
+    // 风险低的操作排在前面
+    suggestions.sort_by_key(|s| s.risk_level);
+
+    PortDiagnosis {
+        port,
+        in_use,
+        summary,
+        sockets,
+        dual_stack,
+        owners,
+        container,
+        suggestions,
+        suggested_port,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listener(process: &ProcessInfo, address: &str) -> ConnectionInfo {
+        ConnectionInfo {
+            protocol: "TCP".to_string(),
+            local_address: address.to_string(),
+            local_port: 8080,
+            remote_address: "0.0.0.0".to_string(),
+            remote_port: 0,
+            state: "LISTENING".to_string(),
+            process: Some(process.clone()),
+        }
+    }
+
+    fn process(pid: u32, parent_pid: Option<u32>) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: "port-test".to_string(),
+            exe_path: None,
+            cmd: Vec::new(),
+            parent_pid,
+        }
+    }
+
+    #[test]
+    fn diagnosis_suggests_stopping_master_instead_of_workers() {
+        let mut monitor = PortMonitor::new();
+        monitor.refresh();
+
+        // 工作进程各自持有SO_REUSEPORT套接字，主进程也在监听
+        let master = process(std::process::id(), None);
+        let workers = [process(u32::MAX - 1, Some(master.pid)), process(u32::MAX - 2, Some(master.pid))];
+        let sockets = vec![
+            listener(&workers[0], "0.0.0.0"),
+            listener(&workers[1], "0.0.0.0"),
+            listener(&master, "::"),
+        ];
+
+        let diagnosis = build_port_diagnosis(&monitor, 8080, sockets, None);
+
+        assert_eq!(diagnosis.owners[0].process.pid, master.pid);
+        assert_eq!(diagnosis.owners.len(), 3);
+        assert!(diagnosis.summary.contains(&master.pid.to_string()));
+
+        let stop_master = diagnosis
+            .suggestions
+            .iter()
+            .find(|s| s.kind == ActionKind::StopProcess { pid: master.pid })
+            .unwrap();
+        assert_eq!(stop_master.action, "停止主进程");
+        assert!(!diagnosis.suggestions.iter().any(|s| matches!(
+            s.kind,
+            ActionKind::StopProcess { pid } | ActionKind::ForceKillProcess { pid } if pid != master.pid
+        )));
+    }
 }
@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use crate::commands::port_allocator::{self, PortReservationStore};
+use crate::commands::port_monitor;
+use crate::monitor::port_history::{PortHistoryRecord, PortHistoryStore};
+use crate::monitor::port_watcher::{PortEvent, PortWatcher};
+use crate::monitor::process_sampler::ProcessSampler;
+use crate::rules::protection_policy::ProtectionPolicyStore;
+
+pub const DEFAULT_INTERVAL_MS: u64 = 2000;
+const MIN_INTERVAL_MS: u64 = 500;
+
+/// 端口监视状态
+///
+/// 监视线程在应用启动后一直运行并写入端口历史，启动和停止只控制是否向前端推送事件，
+/// 因此停止推送不会中断端口历史。
+pub struct PortWatcherState {
+    watcher: Mutex<Option<PortWatcher>>,
+    emitting: Arc<AtomicBool>,
+}
+
+impl PortWatcherState {
+    pub fn new() -> Self {
+        PortWatcherState {
+            watcher: Mutex::new(None),
+            emitting: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// 开始推送事件，采样间隔变化时按新的间隔重新启动监视线程
+    pub fn start(&self, app_handle: AppHandle, interval_ms: u64) -> PortWatcherStatus {
+        let interval_ms = interval_ms.max(MIN_INTERVAL_MS);
+        let interval = Duration::from_millis(interval_ms);
+        let mut watcher = self.watcher.lock().unwrap();
+
+        if watcher.as_ref().map_or(true, |w| w.interval() != interval) {
+            if let Some(existing) = watcher.take() {
+                existing.stop();
+            }
+
+            let emitting = self.emitting.clone();
+            let scan_handle = app_handle.clone();
+            *watcher = Some(PortWatcher::spawn(
+                interval,
+                // 使用采样器的进程快照，不在监视线程中另建进程表
+                move || {
+                    let policy = scan_handle.state::<ProtectionPolicyStore>().policy();
+                    port_monitor::get_listening_ports(&scan_handle.state::<ProcessSampler>(), policy)
+                },
+                move |events| handle_port_events(&app_handle, events, emitting.load(Ordering::SeqCst)),
+            ));
+        }
+
+        self.emitting.store(true, Ordering::SeqCst);
+
+        PortWatcherStatus {
+            running: true,
+            recording_history: true,
+            interval_ms,
+        }
+    }
+
+    /// 停止推送事件，端口历史仍按原间隔记录；返回之前是否在推送
+    pub fn stop(&self) -> bool {
+        self.emitting.swap(false, Ordering::SeqCst)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PortWatcherStatus {
+    /// 是否向前端推送端口事件
+    pub running: bool,
+    /// 监视线程是否在运行并写入端口历史，与是否推送事件无关
+    pub recording_history: bool,
+    pub interval_ms: u64,
+}
+
+#[tauri::command]
+pub async fn start_port_watcher(
+    interval_ms: Option<u64>,
+    app_handle: AppHandle,
+    state: State<'_, PortWatcherState>,
+) -> Result<PortWatcherStatus, String> {
+    Ok(state.start(app_handle, interval_ms.unwrap_or(DEFAULT_INTERVAL_MS)))
+}
+
+#[tauri::command]
+pub async fn stop_port_watcher(state: State<'_, PortWatcherState>) -> Result<bool, String> {
+    Ok(state.stop())
+}
+
+#[tauri::command]
+pub async fn get_port_watcher_status(
+    state: State<'_, PortWatcherState>,
+) -> Result<PortWatcherStatus, String> {
+    let watcher = state.watcher.lock().unwrap();
+
+    Ok(PortWatcherStatus {
+        running: state.emitting.load(Ordering::SeqCst),
+        recording_history: watcher.is_some(),
+        interval_ms: watcher
+            .as_ref()
+            .map_or(DEFAULT_INTERVAL_MS, |w| w.interval().as_millis() as u64),
+    })
+}
+
+// 写入端口历史，开启推送时同时推送事件给前端
+fn handle_port_events(app_handle: &AppHandle, events: Vec<PortEvent>, emit: bool) {
+    let reservations = app_handle.state::<PortReservationStore>().list();
+    let timestamp = chrono::Local::now().timestamp();
+
+    let records = events
+        .iter()
+        .map(|event| PortHistoryRecord::from_event(event, timestamp))
+        .collect();
+    if let Err(e) = app_handle.state::<PortHistoryStore>().append(records) {
+        eprintln!("记录端口历史失败: {}", e);
+    }
+
+    if !emit {
+        return;
+    }
+
+    for mut event in events {
+        // 与get_all_ports返回的数据保持一致，附带端口预留信息
+        let port = match &mut event {
+            PortEvent::Opened(port) | PortEvent::Closed(port) => port,
+            PortEvent::OwnerChanged(change) => &mut change.port,
+        };
+        port_allocator::apply_reservations(std::slice::from_mut(port), &reservations);
+
+        let result = match &event {
+            PortEvent::Opened(port) | PortEvent::Closed(port) => {
+                app_handle.emit_all(event.event_name(), port.clone())
+            }
+            PortEvent::OwnerChanged(change) => app_handle.emit_all(event.event_name(), change.clone()),
+        };
+
+        if let Err(e) = result {
+            eprintln!("推送端口事件失败: {}", e);
+        }
+    }
+}
@@ -1,6 +1,18 @@
-﻿use serde::{Deserialize, Serialize};
-use sysinfo::{System, Pid, Signal, ProcessRefreshKind, RefreshKind};
-use crate::utils::process_utils;
+﻿use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, Process, System, ThreadKind};
+use tauri::State;
+use crate::commands::port_monitor;
+use crate::monitor::audit_log::{AuditLog, AuditRecord};
+use crate::monitor::process_control;
+use crate::monitor::process_detail::{self, AccountCache, ProcessDetail};
+use crate::monitor::process_history::{ProcessHistoryQuery, ProcessHistorySeries};
+use crate::monitor::process_restarter::{self, LaunchSpec, RestartOptions, RestartResult};
+use crate::monitor::process_query::{self, ProcessPage, ProcessQuery};
+use crate::monitor::process_sampler::ProcessSampler;
+use crate::monitor::process_terminator::{self, TerminateOptions, TerminationResult, TerminationTarget};
+use crate::monitor::process_tree::{self, ProcessTreeNode};
+use crate::rules::protection_policy::{ProtectionDecision, ProtectionPolicy, ProtectionPolicyStore};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DetailedProcessInfo {
@@ -12,51 +24,87 @@ pub struct DetailedProcessInfo {
     pub memory_usage: u64,
     pub status: String,
     pub start_time: u64,
+    pub parent_pid: Option<u32>,
+    /// 当前nice值，仅Linux可用
+    pub nice: Option<i32>,
+    /// 是否已被暂停（SIGSTOP）
+    pub stopped: bool,
 }
 
-#[tauri::command]
-pub async fn get_process_info(pid: u32) -> Result<Option<DetailedProcessInfo>, String> {
-    let mut system = System::new_with_specifics(
-        RefreshKind::new().with_processes(ProcessRefreshKind::everything())
-    );
-    system.refresh_processes();
-
-    if let Some(process) = system.process(Pid::from(pid as usize)) {
-        Ok(Some(DetailedProcessInfo {
-            pid,
-            name: process.name().to_string(),
-            exe_path: process.exe().map(|path| path.to_string_lossy().to_string()),
-            cmd: process.cmd().to_vec(),
-            cpu_usage: process.cpu_usage(),
-            memory_usage: process.memory(),
-            status: format!("{:?}", process.status()),
-            start_time: process.start_time(),
-        }))
-    } else {
-        Ok(None)
+/// `nice` 由调用方提供，列出大量进程时使用采样器读取的值，避免逐个读取 /proc
+pub fn to_detailed_info(pid: Pid, process: &Process, nice: Option<i32>) -> DetailedProcessInfo {
+    DetailedProcessInfo {
+        pid: pid.as_u32(),
+        name: process.name().to_string(),
+        exe_path: process.exe().map(|path| path.to_string_lossy().to_string()),
+        cmd: process.cmd().to_vec(),
+        cpu_usage: process.cpu_usage(),
+        memory_usage: process.memory(),
+        status: format!("{:?}", process.status()),
+        start_time: process.start_time(),
+        parent_pid: process.parent().map(|ppid| ppid.as_u32()),
+        nice,
+        stopped: process_control::is_stopped(process),
     }
 }
 
 #[tauri::command]
-pub async fn get_all_processes() -> Result<Vec<DetailedProcessInfo>, String> {
-    let mut system = System::new_with_specifics(
-        RefreshKind::new().with_processes(ProcessRefreshKind::everything())
-    );
-    system.refresh_processes();
+pub async fn get_process_info(
+    pid: u32,
+    sampler: State<'_, ProcessSampler>,
+) -> Result<Option<DetailedProcessInfo>, String> {
+    Ok(find_process_info(&sampler, pid))
+}
+
+fn find_process_info(sampler: &ProcessSampler, pid: u32) -> Option<DetailedProcessInfo> {
+    let system = sampler.snapshot();
+    let nice = sampler.nice_values().get(&pid).copied();
+    system
+        .process(Pid::from(pid as usize))
+        .map(|process| to_detailed_info(Pid::from(pid as usize), process, nice))
+}
+
+/// 获取进程的扩展信息：工作目录、环境变量、线程数、打开的文件、磁盘读写量、用户组和监听端口
+#[tauri::command]
+pub async fn get_process_detail(
+    pid: u32,
+    sampler: State<'_, ProcessSampler>,
+    policy: State<'_, ProtectionPolicyStore>,
+    accounts: State<'_, AccountCache>,
+) -> Result<Option<ProcessDetail>, String> {
+    // 端口扫描较慢，在刷新进程详情之前完成
+    let ports = port_monitor::get_listening_ports(&sampler, policy.policy())?;
+
+    let system = sampler.refresh_process_details(pid);
+    let nice = sampler.nice_values().get(&pid).copied();
+    let pid = Pid::from(pid as usize);
+    Ok(system
+        .process(pid)
+        .map(|process| process_detail::build_process_detail(pid, process, &ports, &accounts, nice)))
+}
+
+/// 获取进程的资源历史，按PID或进程名查询，可指定时间分辨率用于绘制趋势图
+#[tauri::command]
+pub async fn get_process_history(
+    query: ProcessHistoryQuery,
+    sampler: State<'_, ProcessSampler>,
+) -> Result<ProcessHistorySeries, String> {
+    sampler.history().query(&query)
+}
+
+#[tauri::command]
+pub async fn get_all_processes(sampler: State<'_, ProcessSampler>) -> Result<Vec<DetailedProcessInfo>, String> {
+    let system = sampler.snapshot();
+    let nice_values = sampler.nice_values();
     
     let mut processes = Vec::new();
     
+    // 与进程查询和进程树一致，排除Linux上被列为进程的用户态线程
     for (pid, process) in system.processes() {
-        processes.push(DetailedProcessInfo {
-            pid: pid.as_u32(),
-            name: process.name().to_string(),
-            exe_path: process.exe().map(|path| path.to_string_lossy().to_string()),
-            cmd: process.cmd().to_vec(),
-            cpu_usage: process.cpu_usage(),
-            memory_usage: process.memory(),
-            status: format!("{:?}", process.status()),
-            start_time: process.start_time(),
-        });
+        if process.thread_kind() == Some(ThreadKind::Userland) {
+            continue;
+        }
+        processes.push(to_detailed_info(*pid, process, nice_values.get(&pid.as_u32()).copied()));
     }
     
     // 按CPU使用率排序
@@ -70,34 +118,371 @@ pub async fn get_all_processes() -> Result<Vec<DetailedProcessInfo>, String> {
     Ok(processes)
 }
 
+/// 按条件查询进程并分页，返回满足条件的总数
+#[tauri::command]
+pub async fn query_processes(
+    query: Option<ProcessQuery>,
+    sampler: State<'_, ProcessSampler>,
+    policy: State<'_, ProtectionPolicyStore>,
+    accounts: State<'_, AccountCache>,
+) -> Result<ProcessPage, String> {
+    let query = query.unwrap_or_default();
+
+    // 只有按监听端口或项目类型过滤时才扫描端口
+    let ports = if query.needs_ports() {
+        port_monitor::get_listening_ports(&sampler, policy.policy())?
+    } else {
+        Vec::new()
+    };
+
+    let system = sampler.snapshot();
+    process_query::query_processes(&system, &query, &ports, &sampler.nice_values(), &accounts)
+}
+
+/// 终止进程并确认其已退出，宽限期内未退出时升级为强制终止
+#[tauri::command]
+pub async fn kill_process(
+    pid: u32,
+    confirm_token: Option<String>,
+    sampler: State<'_, ProcessSampler>,
+    policy: State<'_, ProtectionPolicyStore>,
+    audit: State<'_, AuditLog>,
+) -> Result<bool, String> {
+    let options = TerminateOptions::default();
+    let result = run_termination(&sampler, &policy, &audit, "kill_process", pid, options, confirm_token).await?;
+    Ok(result.survived.is_empty())
+}
+
+#[tauri::command]
+pub async fn terminate_process(
+    pid: u32,
+    options: Option<TerminateOptions>,
+    confirm_token: Option<String>,
+    sampler: State<'_, ProcessSampler>,
+    policy: State<'_, ProtectionPolicyStore>,
+    audit: State<'_, AuditLog>,
+) -> Result<TerminationResult, String> {
+    let options = options.unwrap_or_default();
+    run_termination(&sampler, &policy, &audit, "terminate_process", pid, options, confirm_token).await
+}
+
+#[tauri::command]
+pub async fn suspend_process(
+    pid: u32,
+    confirm_token: Option<String>,
+    sampler: State<'_, ProcessSampler>,
+    policy: State<'_, ProtectionPolicyStore>,
+    audit: State<'_, AuditLog>,
+) -> Result<(), String> {
+    let record = audit_target(&sampler, "suspend_process", pid);
+    let result = {
+        let system = sampler.refresh_process(pid);
+        authorize(&system, &policy, pid, "suspend", confirm_token.as_deref()).and_then(process_control::suspend)
+    };
+
+    audit.record(record.finish(&result));
+    result
+}
+
+#[tauri::command]
+pub async fn resume_process(
+    pid: u32,
+    confirm_token: Option<String>,
+    sampler: State<'_, ProcessSampler>,
+    policy: State<'_, ProtectionPolicyStore>,
+    audit: State<'_, AuditLog>,
+) -> Result<(), String> {
+    let record = audit_target(&sampler, "resume_process", pid);
+    let result = {
+        let system = sampler.refresh_process(pid);
+        authorize(&system, &policy, pid, "resume", confirm_token.as_deref()).and_then(process_control::resume)
+    };
+
+    audit.record(record.finish(&result));
+    result
+}
+
+#[tauri::command]
+pub async fn set_process_priority(
+    pid: u32,
+    nice: i32,
+    confirm_token: Option<String>,
+    sampler: State<'_, ProcessSampler>,
+    policy: State<'_, ProtectionPolicyStore>,
+    audit: State<'_, AuditLog>,
+) -> Result<(), String> {
+    let record = audit_target(&sampler, "set_process_priority", pid).detail(format!("nice={}", nice));
+    let result = authorize(&sampler.refresh_process(pid), &policy, pid, "set_priority", confirm_token.as_deref())
+        .map(|_| ())
+        .and_then(|_| process_control::set_priority(pid, nice));
+
+    audit.record(record.finish(&result));
+    result
+}
+
+#[tauri::command]
+pub async fn set_process_io_priority(
+    pid: u32,
+    class: String,
+    level: Option<u8>,
+    confirm_token: Option<String>,
+    sampler: State<'_, ProcessSampler>,
+    policy: State<'_, ProtectionPolicyStore>,
+    audit: State<'_, AuditLog>,
+) -> Result<(), String> {
+    let record = audit_target(&sampler, "set_process_io_priority", pid)
+        .detail(format!("class={} level={:?}", class, level));
+    let result = authorize(&sampler.refresh_process(pid), &policy, pid, "set_io_priority", confirm_token.as_deref())
+        .map(|_| ())
+        .and_then(|_| process_control::set_io_priority(pid, &class, level));
+
+    audit.record(record.finish(&result));
+    result
+}
+
+/// 查询对进程执行某个操作时命中的保护规则，需要确认时返回确认令牌
+///
+/// `action` 为 kill、kill_tree、restart、suspend、resume、set_priority 或 set_io_priority。
+/// kill_tree 和 restart 会作用于整个进程树（重启默认同时终止子进程），按子树整体评估。
+#[tauri::command]
+pub async fn check_process_protection(
+    pid: u32,
+    action: String,
+    sampler: State<'_, ProcessSampler>,
+    policy: State<'_, ProtectionPolicyStore>,
+) -> Result<ProtectionDecision, String> {
+    let system = sampler.refresh_process(pid);
+
+    if action == "kill_tree" || action == "restart" {
+        let pids = process_tree::collect_subtree_pids(&system, pid);
+        policy.evaluate_group(&system, pid, &pids, &action)
+    } else {
+        policy.evaluate(&system, pid, &action)
+    }
+}
+
+#[tauri::command]
+pub async fn get_protection_policy(policy: State<'_, ProtectionPolicyStore>) -> Result<ProtectionPolicy, String> {
+    Ok(policy.policy())
+}
+
 #[tauri::command]
-pub async fn kill_process(pid: u32) -> Result<bool, String> {
-    let mut system = System::new_with_specifics(
-        RefreshKind::new().with_processes(ProcessRefreshKind::everything())
-    );
-    system.refresh_processes();
+pub async fn set_protection_policy(
+    new_policy: ProtectionPolicy,
+    policy: State<'_, ProtectionPolicyStore>,
+    audit: State<'_, AuditLog>,
+) -> Result<(), String> {
+    let record = AuditRecord::new("set_protection_policy").detail(format!("{}条规则", new_policy.rules.len()));
+    let result = policy.set_policy(new_policy);
+
+    audit.record(record.finish(&result));
+    result
+}
+
+// 在操作前记录目标进程的名称和命令行，进程被终止后就无法再读取
+fn audit_target(sampler: &ProcessSampler, action: &str, pid: u32) -> AuditRecord {
+    let system = sampler.refresh_process(pid);
+    let process = system.process(Pid::from(pid as usize));
+
+    AuditRecord::new(action).process(
+        pid,
+        process.map(|p| p.name().to_string()),
+        process.map(|p| p.cmd().to_vec()).unwrap_or_default(),
+    )
+}
+
+// 暂停、调整优先级等操作与终止进程使用相同的保护策略
+fn authorize<'a>(
+    system: &'a System,
+    policy: &ProtectionPolicyStore,
+    pid: u32,
+    action: &str,
+    confirm_token: Option<&str>,
+) -> Result<&'a Process, String> {
+    policy.evaluate(system, pid, action)?.enforce(confirm_token)?;
+
+    system
+        .process(Pid::from(pid as usize))
+        .ok_or_else(|| "进程不存在".to_string())
+}
+
+#[tauri::command]
+pub async fn get_process_tree(
+    root_pid: Option<u32>,
+    sampler: State<'_, ProcessSampler>,
+) -> Result<Vec<ProcessTreeNode>, String> {
+    let system = sampler.snapshot();
+
+    Ok(process_tree::build_process_tree(&system, root_pid, &sampler.nice_values()))
+}
+
+#[tauri::command]
+pub async fn kill_process_tree(
+    pid: u32,
+    confirm_token: Option<String>,
+    sampler: State<'_, ProcessSampler>,
+    policy: State<'_, ProtectionPolicyStore>,
+    audit: State<'_, AuditLog>,
+) -> Result<TerminationResult, String> {
+    let options = TerminateOptions {
+        include_tree: true,
+        ..TerminateOptions::default()
+    };
+
+    run_termination(&sampler, &policy, &audit, "kill_process_tree", pid, options, confirm_token).await
+}
 
-    if let Some(process) = system.process(Pid::from(pid as usize)) {
-        // 检查是否是系统关键进程
-        if process_utils::is_system_process(process.name()) {
-            return Err(format!("{}是系统关键进程，终止可能导致系统不稳定", process.name()));
+async fn run_termination(
+    sampler: &ProcessSampler,
+    policy: &ProtectionPolicyStore,
+    audit: &AuditLog,
+    action: &str,
+    pid: u32,
+    options: TerminateOptions,
+    confirm_token: Option<String>,
+) -> Result<TerminationResult, String> {
+    let record = audit_target(sampler, action, pid);
+
+    let policy_action = if options.include_tree { "kill_tree" } else { "kill" };
+    let targets = collect_termination_targets(sampler, policy, pid, options.include_tree, policy_action, confirm_token.as_deref());
+
+    let result = match targets {
+        // 宽限期内需要阻塞轮询，放到独立线程中执行
+        Ok(targets) => tokio::task::spawn_blocking(move || process_terminator::terminate(pid, &targets, &options))
+            .await
+            .map_err(|e| e.to_string()),
+        Err(e) => Err(e),
+    };
+
+    let mut record = record.finish(&result);
+    if let Ok(termination) = &result {
+        record = record.detail(format!(
+            "退出: {:?}，强制终止: {:?}，仍存活: {:?}，耗时{}ms",
+            termination.exited, termination.forced, termination.survived, termination.elapsed_ms
+        ));
+
+        if !termination.survived.is_empty() {
+            record.success = false;
+            record.error = Some("部分进程未能终止".to_string());
         }
-        
-        Ok(process.kill_with(Signal::Term).unwrap_or(false))
+    }
+    audit.record(record);
+
+    result
+}
+
+fn collect_termination_targets(
+    sampler: &ProcessSampler,
+    policy: &ProtectionPolicyStore,
+    pid: u32,
+    include_tree: bool,
+    action: &str,
+    confirm_token: Option<&str>,
+) -> Result<Vec<TerminationTarget>, String> {
+    let system = sampler.refresh_process(pid);
+
+    // 父进程排在子进程之前，避免父进程重新拉起被终止的子进程
+    let pids = if include_tree {
+        process_tree::collect_subtree_pids(&system, pid)
     } else {
-        Err("进程不存在".to_string())
+        vec![pid]
+    };
+
+    // 进程树中只要有一个进程被禁止操作就整体拒绝
+    policy.evaluate_group(&system, pid, &pids, action)?.enforce(confirm_token)?;
+
+    let targets = pids
+        .into_iter()
+        .filter_map(|pid| {
+            system.process(Pid::from(pid as usize)).map(|process| TerminationTarget {
+                pid,
+                start_time: process.start_time(),
+            })
+        })
+        .collect();
+
+    Ok(targets)
+}
+
+/// 以原命令行、工作目录和环境变量重启进程，报告新PID以及原端口是否重新监听
+#[tauri::command]
+pub async fn restart_process(
+    pid: u32,
+    options: Option<RestartOptions>,
+    confirm_token: Option<String>,
+    sampler: State<'_, ProcessSampler>,
+    policy: State<'_, ProtectionPolicyStore>,
+    audit: State<'_, AuditLog>,
+) -> Result<RestartResult, String> {
+    let options = options.unwrap_or_default();
+    let record = audit_target(&sampler, "restart_process", pid);
+    let restart_policy = policy.policy();
+
+    let result = match prepare_restart(&sampler, &policy, pid, &options, confirm_token.as_deref()) {
+        // 终止、等待端口和启动都需要阻塞等待，放到独立线程中执行
+        Ok((spec, targets, ports)) => tokio::task::spawn_blocking(move || {
+            process_restarter::restart(pid, &spec, &targets, &ports, &options, restart_policy)
+        })
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|result| result),
+        Err(e) => Err(e),
+    };
+
+    let mut record = record.finish(&result);
+    if let Ok(restart) = &result {
+        record = record.detail(format!(
+            "新PID: {}，恢复监听: {:?}，未恢复: {:?}",
+            restart.new_pid, restart.restored_ports, restart.missing_ports
+        ));
     }
+    audit.record(record);
+
+    result
+}
+
+// 在终止进程之前记录其监听端口和启动上下文
+fn prepare_restart(
+    sampler: &ProcessSampler,
+    policy: &ProtectionPolicyStore,
+    pid: u32,
+    options: &RestartOptions,
+    confirm_token: Option<&str>,
+) -> Result<(LaunchSpec, Vec<TerminationTarget>, Vec<u16>), String> {
+    let targets = collect_termination_targets(sampler, policy, pid, options.include_tree, "restart", confirm_token)?;
+
+    // 终止整个进程树时，子进程监听的端口也需要等待恢复
+    let pids: HashSet<u32> = targets.iter().map(|target| target.pid).collect();
+    let mut ports: Vec<u16> = port_monitor::get_listening_ports(sampler, policy.policy())?
+        .into_iter()
+        .filter(|port| port.owners.iter().chain(Some(&port.process)).any(|owner| pids.contains(&owner.pid)))
+        .map(|port| port.port)
+        .collect();
+    ports.sort_unstable();
+    ports.dedup();
+
+    let system = sampler.refresh_process_details(pid);
+    let process = system
+        .process(Pid::from(pid as usize))
+        .ok_or_else(|| "进程不存在".to_string())?;
+    let spec = LaunchSpec::capture(process)?;
+
+    Ok((spec, targets, ports))
 }
 
 #[tauri::command]
-pub async fn get_process_by_port(port: u16) -> Result<Option<DetailedProcessInfo>, String> {
+pub async fn get_process_by_port(
+    port: u16,
+    sampler: State<'_, ProcessSampler>,
+    policy: State<'_, ProtectionPolicyStore>,
+) -> Result<Option<DetailedProcessInfo>, String> {
     // 获取所有端口信息
-    let ports = crate::commands::port_monitor::get_all_ports().await?;
+    let ports = port_monitor::get_listening_ports(&sampler, policy.policy())?;
     
-    // 查找指定端口
-    if let Some(port_info) = ports.into_iter().find(|p| p.port == port) {
+    // 查找指定端口，所属进程未知时没有可返回的详细信息
+    if let Some(port_info) = ports.into_iter().find(|p| p.port == port && !p.process.is_unknown()) {
         // 获取进程详细信息
-        get_process_info(port_info.process.pid).await
+        Ok(find_process_info(&sampler, port_info.process.pid))
     } else {
         Ok(None)
     }
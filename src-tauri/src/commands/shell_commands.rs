@@ -1,5 +1,7 @@
 use std::process::Command;
 use serde::{Deserialize, Serialize};
+use tauri::State;
+use crate::monitor::audit_log::{AuditLog, AuditRecord};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SystemDashboardInfo {
@@ -19,7 +21,23 @@ pub struct CommandResult {
 }
 
 #[tauri::command]
-pub async fn run_command(command: String, args: Vec<String>) -> Result<String, String> {
+pub async fn run_command(
+    command: String,
+    args: Vec<String>,
+    audit: State<'_, AuditLog>,
+) -> Result<String, String> {
+    let record = AuditRecord::new("run_command").command(command_line(&command, &args));
+    let result = execute_command(command, args);
+
+    audit.record(record.finish(&result));
+    result
+}
+
+fn command_line(command: &str, args: &[String]) -> Vec<String> {
+    std::iter::once(command.to_string()).chain(args.iter().cloned()).collect()
+}
+
+fn execute_command(command: String, args: Vec<String>) -> Result<String, String> {
     // 在Windows上执行命令，处理编码问题
     #[cfg(target_os = "windows")]
     {
@@ -158,7 +176,15 @@ pub async fn open_system_info() -> Result<(), String> {
 }
 
 #[tauri::command]
-pub async fn restart_explorer() -> Result<(), String> {
+pub async fn restart_explorer(audit: State<'_, AuditLog>) -> Result<(), String> {
+    let record = AuditRecord::new("restart_explorer").command(vec!["explorer.exe".to_string()]);
+    let result = restart_explorer_process();
+
+    audit.record(record.finish(&result));
+    result
+}
+
+fn restart_explorer_process() -> Result<(), String> {
     #[cfg(target_os = "windows")]
     {
         use std::os::windows::process::CommandExt;
@@ -198,7 +224,19 @@ pub async fn clean_temp_files() -> Result<(), String> {
 }
 
 #[tauri::command]
-pub async fn run_command_in_new_window(command: String, args: Vec<String>) -> Result<(), String> {
+pub async fn run_command_in_new_window(
+    command: String,
+    args: Vec<String>,
+    audit: State<'_, AuditLog>,
+) -> Result<(), String> {
+    let record = AuditRecord::new("run_command_in_new_window").command(command_line(&command, &args));
+    let result = spawn_command_window(command, args);
+
+    audit.record(record.finish(&result));
+    result
+}
+
+fn spawn_command_window(command: String, args: Vec<String>) -> Result<(), String> {
     // 在新窗口中执行命令
     #[cfg(target_os = "windows")]
     {
@@ -729,7 +767,7 @@ fn parse_os_info(raw: &str) -> String {
     }
 }
 
-fn open_with_default_app(path: &str) -> Result<(), String> {
+pub fn open_with_default_app(path: &str) -> Result<(), String> {
     #[cfg(target_os = "windows")]
     {
         use std::os::windows::process::CommandExt;
@@ -24,10 +24,36 @@ mod monitor;
 mod rules;
 mod utils;
 
-// No need to import modules that are only used in the invoke_handler
+use commands::port_allocator::PortReservationStore;
+use commands::port_watcher::{PortWatcherState, DEFAULT_INTERVAL_MS};
+use monitor::audit_log::AuditLog;
+use monitor::port_history::PortHistoryStore;
+use monitor::process_detail::AccountCache;
+use monitor::process_sampler::{ProcessSampler, DEFAULT_SAMPLE_INTERVAL};
+use rules::protection_policy::ProtectionPolicyStore;
+use tauri::Manager;
 
 fn main() {
     tauri::Builder::default()
+        .setup(|app| {
+            let data_dir = app
+                .path_resolver()
+                .app_data_dir()
+                .unwrap_or_else(std::env::temp_dir);
+
+            app.manage(PortReservationStore::load(data_dir.join("port_reservations.json")));
+            app.manage(ProtectionPolicyStore::load(data_dir.join("process_protection.json")));
+            app.manage(AuditLog::new(data_dir.join("audit")));
+            app.manage(PortHistoryStore::load(data_dir));
+            app.manage(ProcessSampler::spawn(DEFAULT_SAMPLE_INTERVAL));
+            app.manage(AccountCache::new());
+
+            // 启动时即开始监视端口，保证端口历史连续
+            let watcher = PortWatcherState::new();
+            watcher.start(app.handle(), DEFAULT_INTERVAL_MS);
+            app.manage(watcher);
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
         commands::system_info::get_all_system_info,
         commands::system_info::open_in_explorer,
@@ -39,10 +65,41 @@ fn main() {
         commands::docker_commands::get_container_logs,
         commands::docker_commands::is_docker_available,
         commands::port_monitor::get_all_ports,
+        commands::port_monitor::get_connections,
+        commands::port_monitor::diagnose_port,
+        commands::port_allocator::find_free_ports,
+        commands::port_allocator::reserve_port,
+        commands::port_allocator::release_port,
+        commands::port_allocator::get_port_reservations,
+        commands::port_watcher::start_port_watcher,
+        commands::port_watcher::stop_port_watcher,
+        commands::port_watcher::get_port_watcher_status,
+        commands::port_history::get_port_history,
+        commands::port_history::get_port_history_retention,
+        commands::port_history::set_port_history_retention,
+        commands::port_history::clear_port_history,
         commands::port_monitor::get_port_info,
+        commands::audit_log::get_audit_log,
+        commands::file_monitor::check_file_occupancy,
+        commands::file_monitor::is_file_monitor_supported,
+        commands::actions::execute_action,
         commands::process_analyzer::get_all_processes,
+        commands::process_analyzer::query_processes,
         commands::process_analyzer::get_process_info,
+        commands::process_analyzer::get_process_detail,
+        commands::process_analyzer::get_process_history,
         commands::process_analyzer::kill_process,
+        commands::process_analyzer::terminate_process,
+        commands::process_analyzer::suspend_process,
+        commands::process_analyzer::resume_process,
+        commands::process_analyzer::set_process_priority,
+        commands::process_analyzer::set_process_io_priority,
+        commands::process_analyzer::check_process_protection,
+        commands::process_analyzer::get_protection_policy,
+        commands::process_analyzer::set_protection_policy,
+        commands::process_analyzer::get_process_tree,
+        commands::process_analyzer::kill_process_tree,
+        commands::process_analyzer::restart_process,
         commands::process_analyzer::get_process_by_port,
         commands::shell_commands::run_command,
         commands::shell_commands::open_network_settings,
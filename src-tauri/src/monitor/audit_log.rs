@@ -0,0 +1,303 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, ProcessRefreshKind, System, UpdateKind, Users};
+
+// 单个日志文件超过1MB时轮转，最多保留5个历史文件
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+const MAX_ROTATED_FILES: usize = 5;
+
+/// 一次改变系统状态的操作记录
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuditRecord {
+    pub timestamp: i64,
+    /// 执行操作的操作系统用户，旧版本写入的记录中没有此字段
+    #[serde(default)]
+    pub actor: Option<String>,
+    /// 操作名称，如 kill_process、stop_container、run_command
+    pub action: String,
+    pub target_pid: Option<u32>,
+    pub target_container: Option<String>,
+    pub process_name: Option<String>,
+    pub cmd: Vec<String>,
+    pub success: bool,
+    pub error: Option<String>,
+    /// 操作参数或结果摘要
+    pub detail: Option<String>,
+}
+
+impl AuditRecord {
+    pub fn new(action: &str) -> Self {
+        AuditRecord {
+            timestamp: chrono::Local::now().timestamp(),
+            actor: None,
+            action: action.to_string(),
+            target_pid: None,
+            target_container: None,
+            process_name: None,
+            cmd: Vec::new(),
+            success: false,
+            error: None,
+            detail: None,
+        }
+    }
+
+    pub fn process(mut self, pid: u32, name: Option<String>, cmd: Vec<String>) -> Self {
+        self.target_pid = Some(pid);
+        self.process_name = name;
+        self.cmd = cmd;
+        self
+    }
+
+    pub fn container(mut self, container_id: &str) -> Self {
+        self.target_container = Some(container_id.to_string());
+        self
+    }
+
+    pub fn command(mut self, cmd: Vec<String>) -> Self {
+        self.cmd = cmd;
+        self
+    }
+
+    pub fn detail(mut self, detail: String) -> Self {
+        self.detail = Some(detail);
+        self
+    }
+
+    /// 根据命令的返回值填写结果
+    pub fn finish<T>(mut self, result: &Result<T, String>) -> Self {
+        match result {
+            Ok(_) => self.success = true,
+            Err(e) => {
+                self.success = false;
+                self.error = Some(e.clone());
+            }
+        }
+        self
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct AuditQuery {
+    pub action: Option<String>,
+    pub actor: Option<String>,
+    pub pid: Option<u32>,
+    pub container: Option<String>,
+    /// 进程名子串，不区分大小写
+    pub process_name: Option<String>,
+    pub success: Option<bool>,
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub limit: Option<usize>,
+}
+
+/// 审计日志，以JSON Lines格式追加写入 `audit.jsonl`，超过大小上限后轮转为 `audit.1.jsonl` 等
+pub struct AuditLog {
+    dir: PathBuf,
+    actor: Option<String>,
+    lock: Mutex<()>,
+}
+
+impl AuditLog {
+    pub fn new(dir: PathBuf) -> Self {
+        AuditLog {
+            dir,
+            actor: current_user(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// 追加一条记录，写入失败只打印错误，不影响操作本身
+    pub fn record(&self, mut record: AuditRecord) {
+        if record.actor.is_none() {
+            record.actor = self.actor.clone();
+        }
+
+        if let Err(e) = self.append(&record) {
+            eprintln!("写入审计日志失败: {}", e);
+        }
+    }
+
+    fn append(&self, record: &AuditRecord) -> Result<(), String> {
+        let _guard = self.lock.lock().unwrap();
+
+        fs::create_dir_all(&self.dir).map_err(|e| format!("创建审计日志目录失败: {}", e))?;
+
+        let path = self.file_path(0);
+        if fs::metadata(&path).map_or(false, |m| m.len() >= MAX_FILE_BYTES) {
+            self.rotate()?;
+        }
+
+        let line = serde_json::to_string(record).map_err(|e| format!("序列化审计记录失败: {}", e))?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("打开审计日志失败: {}", e))?;
+
+        writeln!(file, "{}", line).map_err(|e| format!("写入审计日志失败: {}", e))
+    }
+
+    // audit.jsonl -> audit.1.jsonl -> ... -> audit.5.jsonl，最旧的文件被删除
+    fn rotate(&self) -> Result<(), String> {
+        let oldest = self.file_path(MAX_ROTATED_FILES);
+        if oldest.exists() {
+            fs::remove_file(&oldest).map_err(|e| format!("删除旧审计日志失败: {}", e))?;
+        }
+
+        for index in (0..MAX_ROTATED_FILES).rev() {
+            let from = self.file_path(index);
+            if from.exists() {
+                fs::rename(&from, self.file_path(index + 1)).map_err(|e| format!("轮转审计日志失败: {}", e))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn file_path(&self, index: usize) -> PathBuf {
+        if index == 0 {
+            self.dir.join("audit.jsonl")
+        } else {
+            self.dir.join(format!("audit.{}.jsonl", index))
+        }
+    }
+
+    /// 按条件查询审计记录，结果按时间倒序排列
+    pub fn query(&self, query: &AuditQuery) -> Vec<AuditRecord> {
+        let _guard = self.lock.lock().unwrap();
+        let process_name = query.process_name.as_ref().map(|n| n.to_lowercase());
+        let mut records = Vec::new();
+
+        // 从最新的文件开始读取，凑够条数即可停止
+        for index in 0..=MAX_ROTATED_FILES {
+            let content = match fs::read_to_string(self.file_path(index)) {
+                Ok(content) => content,
+                Err(_) => continue,
+            };
+
+            let matched = content
+                .lines()
+                .rev()
+                .filter_map(|line| serde_json::from_str::<AuditRecord>(line).ok())
+                .filter(|r| query.action.as_ref().map_or(true, |action| &r.action == action))
+                .filter(|r| query.actor.as_ref().map_or(true, |actor| r.actor.as_ref() == Some(actor)))
+                .filter(|r| query.pid.map_or(true, |pid| r.target_pid == Some(pid)))
+                .filter(|r| {
+                    query.container.as_ref().map_or(true, |container| {
+                        r.target_container.as_ref().map_or(false, |c| c.starts_with(container.as_str()))
+                    })
+                })
+                .filter(|r| {
+                    process_name.as_ref().map_or(true, |name| {
+                        r.process_name.as_ref().map_or(false, |n| n.to_lowercase().contains(name.as_str()))
+                    })
+                })
+                .filter(|r| query.success.map_or(true, |success| r.success == success))
+                .filter(|r| query.since.map_or(true, |since| r.timestamp >= since))
+                .filter(|r| query.until.map_or(true, |until| r.timestamp <= until));
+
+            records.extend(matched);
+
+            if query.limit.map_or(false, |limit| records.len() >= limit) {
+                break;
+            }
+        }
+
+        records.truncate(query.limit.unwrap_or(usize::MAX));
+        records
+    }
+}
+
+// 运行本程序的操作系统用户，读取不到用户名时使用用户ID
+fn current_user() -> Option<String> {
+    let pid = Pid::from(std::process::id() as usize);
+    let mut system = System::new();
+    system.refresh_process_specifics(pid, ProcessRefreshKind::new().with_user(UpdateKind::Always));
+    let uid = system.process(pid)?.user_id()?;
+
+    let name = Users::new_with_refreshed_list()
+        .get_user_by_id(uid)
+        .map(|user| user.name().to_string());
+    Some(name.unwrap_or_else(|| uid.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_log(name: &str) -> AuditLog {
+        let dir = std::env::temp_dir().join(format!("audit_log_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        AuditLog::new(dir)
+    }
+
+    fn actions(records: &[AuditRecord]) -> Vec<&str> {
+        records.iter().map(|r| r.action.as_str()).collect()
+    }
+
+    #[test]
+    fn records_actor_and_filters_queries() {
+        let log = temp_log("query");
+        log.record(AuditRecord::new("kill_process").process(42, Some("Node".to_string()), Vec::new()).finish(&Ok(())));
+        log.record(AuditRecord::new("stop_container").container("abcdef123456").finish::<()>(&Err("失败".to_string())));
+        log.record(AuditRecord::new("kill_process").process(43, Some("python".to_string()), Vec::new()).finish(&Ok(())));
+
+        let all = log.query(&AuditQuery::default());
+        assert_eq!(actions(&all), vec!["kill_process", "stop_container", "kill_process"]);
+        assert_eq!(all[0].target_pid, Some(43));
+        if cfg!(unix) {
+            assert!(all.iter().all(|r| r.actor.is_some() && r.actor == log.actor));
+        }
+
+        let by_pid = log.query(&AuditQuery { pid: Some(42), ..AuditQuery::default() });
+        assert_eq!(by_pid.len(), 1);
+
+        let by_name = log.query(&AuditQuery { process_name: Some("node".to_string()), ..AuditQuery::default() });
+        assert_eq!(by_name[0].target_pid, Some(42));
+
+        let by_container = log.query(&AuditQuery { container: Some("abc".to_string()), ..AuditQuery::default() });
+        assert_eq!(by_container[0].error.as_deref(), Some("失败"));
+
+        let failed = log.query(&AuditQuery { success: Some(false), ..AuditQuery::default() });
+        assert_eq!(actions(&failed), vec!["stop_container"]);
+
+        let other_actor = log.query(&AuditQuery { actor: Some("no-such-user".to_string()), ..AuditQuery::default() });
+        assert!(other_actor.is_empty());
+
+        let limited = log.query(&AuditQuery { limit: Some(1), ..AuditQuery::default() });
+        assert_eq!(limited[0].target_pid, Some(43));
+
+        fs::remove_dir_all(&log.dir).unwrap();
+    }
+
+    #[test]
+    fn rotates_and_drops_oldest_file() {
+        let log = temp_log("rotate");
+        fs::create_dir_all(&log.dir).unwrap();
+
+        for index in 0..=MAX_ROTATED_FILES {
+            let line = serde_json::to_string(&AuditRecord::new(&format!("action_{}", index))).unwrap();
+            fs::write(log.file_path(index), format!("{}\n", line)).unwrap();
+        }
+
+        // 当前文件超过大小上限，下一次写入前轮转
+        let padding = "x".repeat(MAX_FILE_BYTES as usize);
+        let mut file = OpenOptions::new().append(true).open(log.file_path(0)).unwrap();
+        writeln!(file, "{}", padding).unwrap();
+
+        log.record(AuditRecord::new("latest"));
+
+        let records = log.query(&AuditQuery::default());
+        assert_eq!(
+            actions(&records),
+            vec!["latest", "action_0", "action_1", "action_2", "action_3", "action_4"]
+        );
+        assert!(fs::metadata(log.file_path(0)).unwrap().len() < MAX_FILE_BYTES);
+
+        fs::remove_dir_all(&log.dir).unwrap();
+    }
+}
@@ -1,219 +1,305 @@
 use std::path::Path;
-use sysinfo::{System, RefreshKind};
-use crate::commands::file_monitor::{FileOccupancy, ProcessInfo, Solution};
+use sysinfo::{Pid, System};
+use crate::monitor::file_lock::{self, FileTarget};
+use crate::monitor::model::{ActionKind, ActionSuggestion, FileOccupancy, FileProcessInfo, RiskLevel, Solution};
+use crate::monitor::port;
+use crate::monitor::process_sampler::ProcessSampler;
+use crate::rules::protection_policy::ProtectionPolicy;
+use crate::utils::process_utils;
 
 pub struct FileMonitor {
-    system: System,
+    policy: ProtectionPolicy,
 }
 
 impl FileMonitor {
     pub fn new() -> Self {
-        let system = System::new_with_specifics(
-            RefreshKind::everything()
-        );
-        
         FileMonitor {
-            system,
+            policy: ProtectionPolicy::new(),
         }
     }
-    
-    pub fn refresh(&mut self) {
-        self.system.refresh_all();
+
+    /// 使用用户配置的保护策略调整终止进程的建议，默认使用当前平台的默认规则
+    pub fn with_policy(mut self, policy: ProtectionPolicy) -> Self {
+        self.policy = policy;
+        self
     }
-    
-    pub fn check_file_occupancy(&mut self, file_path: &str) -> Option<FileOccupancy> {
-        self.refresh();
-        
-        // 检查文件是否存在
-        if !Path::new(file_path).exists() {
-            return None;
+
+    /// 检查文件是否被进程打开、映射到内存或锁定，未被占用时返回None
+    ///
+    /// 传入目录时检查其下的全部文件，并包括工作目录位于其中的进程，
+    /// 这些进程都会阻止删除目录或卸载所在的磁盘。
+    ///
+    /// 使用采样器的进程快照解析占用进程，按用户和祖先进程匹配的保护规则需要完整的进程表；
+    /// 快照在扫描完文件描述符之后才锁定，扫描期间不阻塞采样线程。
+    pub fn check_file_occupancy(
+        &self,
+        file_path: &str,
+        sampler: &ProcessSampler,
+    ) -> Result<Option<FileOccupancy>, String> {
+        let path = Path::new(file_path);
+
+        // 检查路径是否存在
+        if !path.exists() {
+            return Err(format!("路径 {} 不存在", file_path));
         }
-        
-        // 在Windows上，我们需要使用特殊的API来检查文件占用
-        // 这里是一个简单的模拟实现，实际项目中需要使用Windows API
-        
-        // 模拟检测文件占用
-        self.detect_file_occupancy(file_path)
-    }
-    
-    // 这个函数在实际项目中需要使用Windows API实现
-    fn detect_file_occupancy(&self, file_path: &str) -> Option<FileOccupancy> {
-        // 这里只是一个模拟实现，实际项目中需要使用Windows API
-        // 例如，可以使用RestartManager API或其他方法
-        
-        // 模拟一些常见的文件占用情况
-        let file_name = Path::new(file_path)
+
+        let target = FileTarget::new(path)?;
+        let users = file_lock::find_file_users(&target)?;
+        if users.is_empty() {
+            return Ok(None);
+        }
+
+        let pids: Vec<u32> = users.keys().copied().collect();
+        let system = sampler.snapshot_including(&pids);
+
+        let mut processes: Vec<FileProcessInfo> = users
+            .into_iter()
+            .map(|(pid, mut usage)| {
+                let process = system.process(Pid::from(pid as usize));
+
+                usage.paths.sort();
+
+                let name = process.map(|p| p.name().to_string()).unwrap_or_else(|| format!("PID {}", pid));
+                FileProcessInfo {
+                    pid,
+                    friendly_name: process_utils::get_friendly_process_name(&name),
+                    name,
+                    exe_path: process.and_then(|p| p.exe()).map(|exe| exe.to_string_lossy().to_string()),
+                    access_mode: usage.access_mode().to_string(),
+                    fds: usage.fds,
+                    paths: usage.paths,
+                    memory_mapped: usage.memory_mapped,
+                    uses_as_cwd: usage.cwd,
+                    locks: usage.locks,
+                }
+            })
+            .collect();
+
+        // 持有锁和写入文件的进程影响最大，排在前面
+        processes.sort_by_key(|p| (p.locks.is_empty(), !is_writing(p), p.pid));
+
+        let file_name = path
             .file_name()
             .and_then(|n| n.to_str())
-            .unwrap_or("unknown");
-            
-        let extension = Path::new(file_path)
-            .extension()
-            .and_then(|e| e.to_str())
-            .unwrap_or("");
-            
-        // 根据文件扩展名模拟不同的占用情况
-        match extension {
-            "pdf" => {
-                // 模拟PDF文件被Adobe Reader占用
-                let process = ProcessInfo {
-                    pid: 9999, // 模拟PID
-                    name: "AcroRd32.exe".to_string(),
-                    friendly_name: "Adobe Acrobat Reader".to_string(),
-                    exe_path: Some("C:\\Program Files (x86)\\Adobe\\Acrobat Reader DC\\Reader\\AcroRd32.exe".to_string()),
-                };
-                
-                let processes = vec![process.clone()];
-                let (reason, impact, solutions) = self.generate_reason_and_solutions(file_path, &processes);
-                
-                Some(FileOccupancy {
-                    file_path: file_path.to_string(),
-                    file_name: file_name.to_string(),
-                    occupied_by: processes,
-                    reason,
-                    impact,
-                    solutions,
-                })
-            },
-            "docx" | "doc" => {
-                // 模拟Word文档被Office占用
-                let process = ProcessInfo {
-                    pid: 9998, // 模拟PID
-                    name: "WINWORD.EXE".to_string(),
-                    friendly_name: "Microsoft Word".to_string(),
-                    exe_path: Some("C:\\Program Files\\Microsoft Office\\root\\Office16\\WINWORD.EXE".to_string()),
-                };
-                
-                let processes = vec![process.clone()];
-                let (reason, impact, solutions) = self.generate_reason_and_solutions(file_path, &processes);
-                
-                Some(FileOccupancy {
-                    file_path: file_path.to_string(),
-                    file_name: file_name.to_string(),
-                    occupied_by: processes,
-                    reason,
-                    impact,
-                    solutions,
-                })
-            },
-            "xlsx" | "xls" => {
-                // 模拟Excel文件被Office占用
-                let process = ProcessInfo {
-                    pid: 9997, // 模拟PID
-                    name: "EXCEL.EXE".to_string(),
-                    friendly_name: "Microsoft Excel".to_string(),
-                    exe_path: Some("C:\\Program Files\\Microsoft Office\\root\\Office16\\EXCEL.EXE".to_string()),
-                };
-                
-                let processes = vec![process.clone()];
-                let (reason, impact, solutions) = self.generate_reason_and_solutions(file_path, &processes);
-                
-                Some(FileOccupancy {
-                    file_path: file_path.to_string(),
-                    file_name: file_name.to_string(),
-                    occupied_by: processes,
-                    reason,
-                    impact,
-                    solutions,
-                })
-            },
-            _ => None, // 其他类型文件暂不模拟占用
+            .unwrap_or("unknown")
+            .to_string();
+
+        let (reason, impact, solutions) =
+            generate_reason_and_solutions(&processes, target.is_directory, &self.policy, &system);
+
+        Ok(Some(FileOccupancy {
+            file_path: file_path.to_string(),
+            file_name,
+            is_directory: target.is_directory,
+            occupied_by: processes,
+            reason,
+            impact,
+            solutions,
+        }))
+    }
+}
+
+fn is_writing(process: &FileProcessInfo) -> bool {
+    process.access_mode.contains("write") || process.locks.iter().any(|lock| lock.access == "WRITE")
+}
+
+fn generate_reason_and_solutions(
+    processes: &[FileProcessInfo],
+    is_directory: bool,
+    policy: &ProtectionPolicy,
+    system: &System,
+) -> (String, String, Vec<Solution>) {
+    let process = match processes.first() {
+        Some(process) => process,
+        None => return ("未知原因".to_string(), "Low".to_string(), vec![]),
+    };
+
+    let holder = format!("{} (PID {})", process.friendly_name, process.pid);
+    let subject = if is_directory { "目录中的文件" } else { "文件" };
+    let mut reason = if let Some(lock) = process.locks.first() {
+        let kind = if lock.access == "WRITE" { "写锁" } else { "读锁" };
+        format!("{} 持有{}的{}（{}）", holder, subject, kind, lock.lock_type)
+    } else {
+        match process.access_mode.as_str() {
+            "write" | "read_write" => format!("{}正在被 {} 写入", subject, holder),
+            "read" => format!("{}正在被 {} 读取", subject, holder),
+            "cwd" => format!("{} 正在以该目录为工作目录", holder),
+            _ => format!("{}被 {} 映射到内存，如已加载的动态库", subject, holder),
         }
+    };
+    if processes.len() > 1 {
+        let target = if is_directory { "该目录" } else { "该文件" };
+        reason.push_str(&format!("，共有 {} 个进程占用{}", processes.len(), target));
     }
-    
-    fn generate_reason_and_solutions(&self, _file_path: &str, processes: &[ProcessInfo]) -> (String, String, Vec<Solution>) {
-        if processes.is_empty() {
-            return (
-                "未知原因".to_string(),
-                "Low".to_string(),
-                vec![],
-            );
+
+    // 以目录为工作目录的进程同样会导致目录无法删除、磁盘无法卸载
+    let impact = if processes.iter().any(is_writing) {
+        "High"
+    } else if processes
+        .iter()
+        .any(|p| !p.locks.is_empty() || !p.fds.is_empty() || p.uses_as_cwd)
+    {
+        "Medium"
+    } else {
+        "Low"
+    };
+
+    let mut solutions = Vec::new();
+    for process in processes {
+        let writing = is_writing(process);
+
+        if process.access_mode == "cwd" {
+            solutions.push(Solution {
+                action: "切换工作目录".to_string(),
+                description: format!("在{}中切换到其他目录，如终端中执行 cd ~", process.friendly_name),
+                risk_level: RiskLevel::Low,
+                kind: ActionKind::Manual,
+                command: None,
+            });
+        } else {
+            solutions.push(Solution {
+                action: format!("关闭{}", process.friendly_name),
+                description: format!("在{}中保存并关闭文件后再进行操作", process.friendly_name),
+                risk_level: RiskLevel::Low,
+                kind: ActionKind::Manual,
+                command: None,
+            });
         }
-        
-        let process = &processes[0];
-        
-        // 根据进程类型生成不同的原因和解决方案
-        match process.name.as_str() {
-            "AcroRd32.exe" => {
-                (
-                    "文件正在被PDF阅读器查看，无法删除或移动".to_string(),
-                    "Medium".to_string(),
-                    vec![
-                        Solution {
-                            action: "关闭PDF阅读器".to_string(),
-                            description: "关闭Adobe Acrobat Reader后再进行文件操作".to_string(),
-                            risk_level: "低".to_string(),
-                            command: None,
-                        },
-                        Solution {
-                            action: "强制关闭".to_string(),
-                            description: "强制终止Adobe Reader进程，可能导致未保存的更改丢失".to_string(),
-                            risk_level: "中".to_string(),
-                            command: Some(format!("taskkill /F /PID {}", process.pid)),
-                        },
-                    ],
-                )
-            },
-            "WINWORD.EXE" => {
-                (
-                    "文件正在被Microsoft Word编辑，无法删除或移动".to_string(),
-                    "High".to_string(),
-                    vec![
-                        Solution {
-                            action: "保存并关闭Word".to_string(),
-                            description: "在Word中保存文档并关闭后再进行文件操作".to_string(),
-                            risk_level: "低".to_string(),
-                            command: None,
-                        },
-                        Solution {
-                            action: "强制关闭".to_string(),
-                            description: "强制终止Word进程，可能导致未保存的更改丢失".to_string(),
-                            risk_level: "高".to_string(),
-                            command: Some(format!("taskkill /F /PID {}", process.pid)),
-                        },
-                    ],
-                )
-            },
-            "EXCEL.EXE" => {
-                (
-                    "文件正在被Microsoft Excel编辑，无法删除或移动".to_string(),
-                    "High".to_string(),
-                    vec![
-                        Solution {
-                            action: "保存并关闭Excel".to_string(),
-                            description: "在Excel中保存文档并关闭后再进行文件操作".to_string(),
-                            risk_level: "低".to_string(),
-                            command: None,
-                        },
-                        Solution {
-                            action: "强制关闭".to_string(),
-                            description: "强制终止Excel进程，可能导致未保存的更改丢失".to_string(),
-                            risk_level: "高".to_string(),
-                            command: Some(format!("taskkill /F /PID {}", process.pid)),
-                        },
-                    ],
-                )
-            },
-            _ => {
-                (
-                    format!("文件正在被{}使用，无法删除或移动", process.friendly_name),
-                    "Medium".to_string(),
-                    vec![
-                        Solution {
-                            action: "关闭应用".to_string(),
-                            description: format!("关闭{}后再进行文件操作", process.friendly_name),
-                            risk_level: "低".to_string(),
-                            command: None,
-                        },
-                        Solution {
-                            action: "强制关闭".to_string(),
-                            description: format!("强制终止{}进程，可能导致未保存的更改丢失", process.friendly_name),
-                            risk_level: "中".to_string(),
-                            command: Some(format!("taskkill /F /PID {}", process.pid)),
-                        },
-                    ],
-                )
+
+        // 本程序自身打开的文件只能由用户在本程序中关闭
+        if process.pid == std::process::id() {
+            continue;
+        }
+
+        let terminate = ActionSuggestion::new(
+            "终止进程",
+            if writing {
+                format!("终止{} (PID {})，正在写入的数据可能丢失", process.friendly_name, process.pid)
+            } else {
+                format!("终止{} (PID {})释放{}", process.friendly_name, process.pid, if is_directory { "目录" } else { "文件" })
             },
+            if writing { RiskLevel::High } else { RiskLevel::Medium },
+            ActionKind::StopProcess { pid: process.pid },
+        );
+
+        // 与端口诊断一样按保护策略调整，进程已退出时无法评估，按未受保护处理
+        let suggestions = match policy.evaluate(system, process.pid, "kill") {
+            Ok(decision) => port::apply_protection(vec![terminate], &decision),
+            Err(_) => vec![terminate],
+        };
+
+        for suggestion in suggestions {
+            let command = match suggestion.kind {
+                ActionKind::StopProcess { pid } => Some(format!("kill {}", pid)),
+                _ => None,
+            };
+
+            solutions.push(Solution {
+                action: suggestion.action,
+                description: suggestion.description,
+                risk_level: suggestion.risk_level,
+                kind: suggestion.kind,
+                command,
+            });
         }
     }
-}
\ No newline at end of file
+
+    (reason, impact.to_string(), solutions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File};
+    use std::process::Command;
+    use std::time::Duration;
+    use crate::rules::protection_policy::{ProtectionMatcher, ProtectionRule, ProtectionSeverity};
+
+    fn holder(pid: u32) -> FileProcessInfo {
+        FileProcessInfo {
+            pid,
+            name: "holder".to_string(),
+            friendly_name: "holder".to_string(),
+            exe_path: None,
+            access_mode: "read".to_string(),
+            fds: vec![3],
+            paths: Vec::new(),
+            memory_mapped: false,
+            uses_as_cwd: false,
+            locks: Vec::new(),
+        }
+    }
+
+    fn kinds(solutions: &[Solution]) -> Vec<&ActionKind> {
+        solutions.iter().map(|s| &s.kind).collect()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn termination_solutions_follow_protection_policy() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = child.id();
+        let mut system = System::new();
+        system.refresh_process(Pid::from(pid as usize));
+
+        let unprotected = ProtectionPolicy::from_rules(Vec::new());
+        let (_, _, solutions) = generate_reason_and_solutions(&[holder(pid)], false, &unprotected, &system);
+        assert_eq!(kinds(&solutions), vec![&ActionKind::Manual, &ActionKind::StopProcess { pid }]);
+        assert_eq!(solutions[1].command, Some(format!("kill {}", pid)));
+
+        let blocked = ProtectionPolicy::from_rules(vec![ProtectionRule {
+            id: "test".to_string(),
+            description: "测试保护".to_string(),
+            matcher: ProtectionMatcher::Pid(vec![pid]),
+            severity: ProtectionSeverity::Block,
+        }]);
+        let (_, _, solutions) = generate_reason_and_solutions(&[holder(pid)], false, &blocked, &system);
+        assert_eq!(kinds(&solutions), vec![&ActionKind::Manual, &ActionKind::ShowProcessDetail { pid }]);
+        assert!(solutions.iter().all(|s| s.command.is_none()));
+
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn protection_rules_see_ancestors_of_file_holders() {
+        let dir = std::env::temp_dir().join(format!("file_monitor_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("held.txt");
+        fs::write(&path, "held").unwrap();
+
+        // 子进程以该文件作为标准输入，采样器启动后才出现，需要由快照补充读取
+        let sampler = ProcessSampler::spawn(Duration::from_secs(60));
+        let mut child = Command::new("sleep").arg("30").stdin(File::open(&path).unwrap()).spawn().unwrap();
+        let pid = child.id();
+
+        let name = sampler.snapshot().process(Pid::from(std::process::id() as usize)).unwrap().name().to_string();
+        let monitor = FileMonitor::new().with_policy(ProtectionPolicy::from_rules(vec![ProtectionRule {
+            id: "ancestor".to_string(),
+            description: "测试进程派生".to_string(),
+            matcher: ProtectionMatcher::Ancestor(vec![name]),
+            severity: ProtectionSeverity::Block,
+        }]));
+
+        let occupancy = monitor.check_file_occupancy(path.to_str().unwrap(), &sampler).unwrap().unwrap();
+        assert!(occupancy.occupied_by.iter().any(|p| p.pid == pid));
+        assert!(kinds(&occupancy.solutions).contains(&&ActionKind::ShowProcessDetail { pid }));
+        assert!(!kinds(&occupancy.solutions).contains(&&ActionKind::StopProcess { pid }));
+
+        let _ = child.kill();
+        let _ = child.wait();
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn never_suggests_terminating_itself() {
+        let pid = std::process::id();
+        let mut system = System::new();
+        system.refresh_process(Pid::from(pid as usize));
+
+        let policy = ProtectionPolicy::from_rules(Vec::new());
+        let (_, _, solutions) = generate_reason_and_solutions(&[holder(pid)], false, &policy, &system);
+        assert_eq!(kinds(&solutions), vec![&ActionKind::Manual]);
+    }
+}
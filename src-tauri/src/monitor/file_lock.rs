@@ -0,0 +1,522 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::monitor::model::FileLock;
+
+// open(2)的访问模式位
+const O_ACCMODE: u32 = 0o3;
+const O_WRONLY: u32 = 0o1;
+const O_RDWR: u32 = 0o2;
+
+/// 文件在内核中的标识，/proc 下的fd、maps和locks都通过设备号和inode定位文件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileIdentity {
+    pub major: u32,
+    pub minor: u32,
+    pub inode: u64,
+}
+
+impl FileIdentity {
+    pub fn of(path: &Path) -> Result<Self, String> {
+        let metadata = fs::metadata(path).map_err(|e| format!("无法读取 {} 的信息: {}", path.display(), e))?;
+        let (dev, inode) = device_and_inode(&metadata);
+
+        Ok(Self::from_dev(dev, inode))
+    }
+
+    // 按glibc的gnu_dev_major/gnu_dev_minor拆分st_dev
+    fn from_dev(dev: u64, inode: u64) -> Self {
+        FileIdentity {
+            major: (((dev >> 8) & 0xfff) | ((dev >> 32) & 0xffff_f000)) as u32,
+            minor: ((dev & 0xff) | ((dev >> 12) & 0xffff_ff00)) as u32,
+            inode,
+        }
+    }
+}
+
+/// 检测目标：单个文件按inode匹配，目录按规范化路径前缀匹配其下的全部文件
+#[derive(Debug, Clone)]
+pub struct FileTarget {
+    pub path: PathBuf,
+    pub identity: FileIdentity,
+    pub is_directory: bool,
+}
+
+impl FileTarget {
+    pub fn new(path: &Path) -> Result<Self, String> {
+        // /proc中的路径都是解析过符号链接的绝对路径
+        let path = fs::canonicalize(path).map_err(|e| format!("无法解析路径 {}: {}", path.display(), e))?;
+
+        Ok(FileTarget {
+            identity: FileIdentity::of(&path)?,
+            is_directory: path.is_dir(),
+            path,
+        })
+    }
+
+    fn matches_path(&self, path: &Path) -> bool {
+        self.is_directory && path.starts_with(&self.path)
+    }
+}
+
+#[cfg(unix)]
+fn device_and_inode(metadata: &fs::Metadata) -> (u64, u64) {
+    use std::os::unix::fs::MetadataExt;
+    (metadata.dev(), metadata.ino())
+}
+
+#[cfg(not(unix))]
+fn device_and_inode(_metadata: &fs::Metadata) -> (u64, u64) {
+    (0, 0)
+}
+
+/// 单个进程对目标文件的使用情况
+#[derive(Debug, Default)]
+pub struct FileUsage {
+    pub fds: Vec<u32>,
+    /// 被占用的文件路径，检测目录时为其下的各个文件
+    pub paths: Vec<String>,
+    pub read: bool,
+    pub write: bool,
+    pub memory_mapped: bool,
+    /// 进程的工作目录位于目标目录下
+    pub cwd: bool,
+    pub locks: Vec<FileLock>,
+}
+
+impl FileUsage {
+    /// read、write、read_write；没有打开的描述符时，仅映射到内存为 mapped，
+    /// 仅以其为工作目录为 cwd，只持有锁为 none
+    pub fn access_mode(&self) -> &'static str {
+        match (self.read, self.write) {
+            (true, true) => "read_write",
+            (false, true) => "write",
+            (true, false) => "read",
+            _ if self.memory_mapped => "mapped",
+            _ if self.cwd => "cwd",
+            _ => "none",
+        }
+    }
+
+    fn add_path(&mut self, path: &Path) {
+        let path = path.to_string_lossy().to_string();
+        if !self.paths.contains(&path) {
+            self.paths.push(path);
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.fds.is_empty() && !self.memory_mapped && !self.cwd && self.locks.is_empty()
+    }
+}
+
+/// 当前平台是否支持文件占用检测，依赖Linux的 /proc
+pub fn is_supported() -> bool {
+    cfg!(target_os = "linux")
+}
+
+/// 扫描全部进程，找出打开、映射或锁定了目标文件的进程（仅Linux）
+///
+/// 目标为目录时还会找出工作目录位于其下的进程。读取其他用户进程的 fd 和 maps 需要root权限，
+/// 无权限的进程会被跳过；检测单个文件时 `/proc/locks` 对所有用户可读，锁的持有者总能被找到。
+pub fn find_file_users(target: &FileTarget) -> Result<BTreeMap<u32, FileUsage>, String> {
+    if !is_supported() {
+        return Err("文件占用检测目前仅支持Linux".to_string());
+    }
+
+    let entries = fs::read_dir("/proc").map_err(|e| format!("无法读取/proc: {}", e))?;
+    let mut users: BTreeMap<u32, FileUsage> = BTreeMap::new();
+    // 被打开的文件的inode，用于在 /proc/locks 中找到目录下文件的锁
+    let mut held: HashSet<FileIdentity> = HashSet::new();
+    held.insert(target.identity);
+
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let pid: u32 = match entry.file_name().to_str().and_then(|name| name.parse().ok()) {
+            Some(pid) => pid,
+            None => continue,
+        };
+
+        let mut usage = FileUsage::default();
+        scan_fds(pid, target, &mut usage, &mut held);
+        scan_maps(pid, target, &mut usage);
+
+        if target.is_directory {
+            let cwd = fs::read_link(format!("/proc/{}/cwd", pid));
+            usage.cwd = cwd.map_or(false, |cwd| target.matches_path(&cwd));
+        }
+
+        if !usage.is_empty() {
+            users.insert(pid, usage);
+        }
+    }
+
+    // flock锁在持有者退出后可能仍由其子进程继承，只保留仍然存在的进程
+    if let Ok(locks) = fs::read_to_string("/proc/locks") {
+        for (pid, identity, lock) in locks.lines().filter_map(parse_lock_line) {
+            if pid > 0 && held.contains(&identity) && Path::new(&format!("/proc/{}", pid)).exists() {
+                users.entry(pid as u32).or_default().locks.push(lock);
+            }
+        }
+    }
+
+    Ok(users)
+}
+
+// 通过 /proc/<pid>/fd 找到指向目标文件的描述符，并从fdinfo读取访问模式
+fn scan_fds(pid: u32, target: &FileTarget, usage: &mut FileUsage, held: &mut HashSet<FileIdentity>) {
+    let entries = match fs::read_dir(format!("/proc/{}/fd", pid)) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let fd: u32 = match entry.file_name().to_str().and_then(|name| name.parse().ok()) {
+            Some(fd) => fd,
+            None => continue,
+        };
+
+        // 跳过socket、pipe等非文件描述符，避免无谓的stat
+        let link = match fs::read_link(entry.path()) {
+            Ok(link) if link.is_absolute() => link,
+            _ => continue,
+        };
+
+        // 检测目录时按路径筛选；检测单个文件时stat会跟随链接到实际文件，硬链接和已改名的文件也能匹配
+        if target.is_directory && !target.matches_path(&link) {
+            continue;
+        }
+
+        let identity = match fs::metadata(entry.path()) {
+            Ok(metadata) => {
+                let (dev, inode) = device_and_inode(&metadata);
+                FileIdentity::from_dev(dev, inode)
+            }
+            Err(_) => continue,
+        };
+        if !target.is_directory && identity != target.identity {
+            continue;
+        }
+
+        usage.fds.push(fd);
+        usage.add_path(&link);
+        held.insert(identity);
+
+        let fdinfo = fs::read_to_string(format!("/proc/{}/fdinfo/{}", pid, fd)).unwrap_or_default();
+        match parse_fdinfo_flags(&fdinfo).map(|flags| flags & O_ACCMODE) {
+            Some(O_WRONLY) => usage.write = true,
+            Some(O_RDWR) => {
+                usage.read = true;
+                usage.write = true;
+            }
+            Some(_) => usage.read = true,
+            None => {}
+        }
+
+        // OFD锁在 /proc/locks 中的pid为-1，只能从持有它的描述符的fdinfo中找到
+        let ofd_locks = fdinfo
+            .lines()
+            .filter_map(|line| line.strip_prefix("lock:"))
+            .filter_map(parse_lock_line)
+            .filter(|(lock_pid, lock_identity, _)| *lock_pid < 0 && *lock_identity == identity)
+            .map(|(_, _, lock)| lock);
+        usage.locks.extend(ofd_locks);
+    }
+}
+
+fn parse_fdinfo_flags(fdinfo: &str) -> Option<u32> {
+    let flags = fdinfo.lines().find_map(|line| line.strip_prefix("flags:"))?;
+    u32::from_str_radix(flags.trim(), 8).ok()
+}
+
+// /proc/<pid>/maps 每行格式：地址 权限 偏移 主:次设备号 inode 路径
+fn scan_maps(pid: u32, target: &FileTarget, usage: &mut FileUsage) {
+    let maps = match fs::read_to_string(format!("/proc/{}/maps", pid)) {
+        Ok(maps) => maps,
+        Err(_) => return,
+    };
+
+    for line in maps.lines() {
+        let fields: Vec<&str> = line.splitn(6, char::is_whitespace).collect();
+        if fields.len() < 6 {
+            continue;
+        }
+
+        // 路径前有对齐用的空格，路径本身可能包含空格
+        let path = Path::new(fields[5].trim_start());
+        let matched = if target.is_directory {
+            target.matches_path(path)
+        } else {
+            parse_identity(fields[3], fields[4]) == Some(target.identity)
+        };
+
+        if matched {
+            usage.memory_mapped = true;
+            usage.add_path(path);
+        }
+    }
+}
+
+/// 解析 /proc/locks 的一行，返回持有者PID（OFD锁为-1）、被锁定的文件和锁信息
+///
+/// 格式如 `1: POSIX  ADVISORY  WRITE 1234 08:01:393219 0 EOF`，
+/// 等待中的锁以 `->` 标记，不属于持有者，返回None。
+pub fn parse_lock_line(line: &str) -> Option<(i64, FileIdentity, FileLock)> {
+    let mut fields = line.split_whitespace().skip(1);
+
+    let lock_type = fields.next()?;
+    if lock_type == "->" {
+        return None;
+    }
+
+    let mode = fields.next()?;
+    let access = fields.next()?;
+    let pid: i64 = fields.next()?.parse().ok()?;
+
+    let (device, inode) = fields.next()?.rsplit_once(':')?;
+    let identity = parse_identity(device, inode)?;
+
+    let start = fields.next()?.parse().ok()?;
+    let end = match fields.next()? {
+        "EOF" => None,
+        end => Some(end.parse().ok()?),
+    };
+
+    Some((
+        pid,
+        identity,
+        FileLock {
+            lock_type: lock_type.to_string(),
+            mandatory: mode == "MANDATORY",
+            access: access.to_string(),
+            start,
+            end,
+        },
+    ))
+}
+
+// 设备号为十六进制的 主:次，inode为十进制
+fn parse_identity(device: &str, inode: &str) -> Option<FileIdentity> {
+    let (major, minor) = device.split_once(':')?;
+
+    Some(FileIdentity {
+        major: u32::from_str_radix(major, 16).ok()?,
+        minor: u32::from_str_radix(minor, 16).ok()?,
+        inode: inode.parse().ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_posix_lock() {
+        let (pid, identity, lock) = parse_lock_line("1: POSIX  ADVISORY  WRITE 1234 08:01:393219 0 EOF").unwrap();
+
+        assert_eq!(pid, 1234);
+        assert_eq!(identity, FileIdentity { major: 8, minor: 1, inode: 393219 });
+        assert_eq!(lock.lock_type, "POSIX");
+        assert!(!lock.mandatory);
+        assert_eq!(lock.access, "WRITE");
+        assert_eq!(lock.start, 0);
+        assert_eq!(lock.end, None);
+    }
+
+    #[test]
+    fn parses_lock_ranges_and_devices() {
+        let (pid, identity, lock) = parse_lock_line("3: FLOCK  MANDATORY READ  42 103:0a:17 100 199").unwrap();
+
+        assert_eq!(pid, 42);
+        assert_eq!(identity, FileIdentity { major: 0x103, minor: 0x0a, inode: 17 });
+        assert!(lock.mandatory);
+        assert_eq!(lock.access, "READ");
+        assert_eq!((lock.start, lock.end), (100, Some(199)));
+    }
+
+    #[test]
+    fn parses_ofd_lock_from_fdinfo() {
+        let line = "lock:\t2: OFDLCK ADVISORY  WRITE -1 00:2a:5678 0 EOF";
+        let (pid, identity, lock) = parse_lock_line(line.strip_prefix("lock:").unwrap()).unwrap();
+
+        assert_eq!(pid, -1);
+        assert_eq!(identity.inode, 5678);
+        assert_eq!(lock.lock_type, "OFDLCK");
+    }
+
+    #[test]
+    fn skips_waiting_and_malformed_locks() {
+        assert!(parse_lock_line("1: -> POSIX  ADVISORY  WRITE 4321 08:01:393219 0 EOF").is_none());
+        assert!(parse_lock_line("1: POSIX  ADVISORY  WRITE 1234 08:01 0 EOF").is_none());
+        assert!(parse_lock_line("1: POSIX  ADVISORY  WRITE 1234 08:01:393219 0 end").is_none());
+        assert!(parse_lock_line("").is_none());
+    }
+
+    #[test]
+    fn parses_fdinfo_flags() {
+        assert_eq!(parse_fdinfo_flags("pos:\t0\nflags:\t0100002\nmnt_id:\t25\n"), Some(0o100002));
+        assert_eq!(parse_fdinfo_flags("pos:\t0\n"), None);
+    }
+
+    #[test]
+    fn describes_access_modes() {
+        let mut usage = FileUsage { memory_mapped: true, cwd: true, ..FileUsage::default() };
+        assert_eq!(usage.access_mode(), "mapped");
+
+        usage.memory_mapped = false;
+        assert_eq!(usage.access_mode(), "cwd");
+
+        usage.read = true;
+        assert_eq!(usage.access_mode(), "read");
+
+        usage.write = true;
+        assert_eq!(usage.access_mode(), "read_write");
+    }
+
+    #[cfg(target_os = "linux")]
+    mod proc {
+        use super::*;
+        use std::fs::{File, OpenOptions};
+        use std::os::unix::fs::MetadataExt;
+        use std::os::unix::io::AsRawFd;
+        use std::process::Command;
+
+        fn temp_file(name: &str) -> PathBuf {
+            let path = std::env::temp_dir().join(format!("file_lock_{}_{}", name, std::process::id()));
+            fs::write(&path, vec![0u8; 4096]).unwrap();
+            path
+        }
+
+        fn own_usage(path: &Path) -> FileUsage {
+            let target = FileTarget::new(path).unwrap();
+            find_file_users(&target)
+                .unwrap()
+                .remove(&std::process::id())
+                .unwrap_or_default()
+        }
+
+        fn fcntl_lock(file: &File, command: libc::c_int, start: i64, len: i64) {
+            let mut lock: libc::flock = unsafe { std::mem::zeroed() };
+            lock.l_type = libc::F_WRLCK as libc::c_short;
+            lock.l_whence = libc::SEEK_SET as libc::c_short;
+            lock.l_start = start;
+            lock.l_len = len;
+
+            assert_eq!(unsafe { libc::fcntl(file.as_raw_fd(), command, &lock) }, 0);
+        }
+
+        #[test]
+        fn splits_device_numbers_like_libc() {
+            let metadata = fs::metadata(std::env::current_exe().unwrap()).unwrap();
+            let identity = FileIdentity::from_dev(metadata.dev(), metadata.ino());
+
+            assert_eq!(identity.major, libc::major(metadata.dev()));
+            assert_eq!(identity.minor, libc::minor(metadata.dev()));
+            assert_eq!(identity.inode, metadata.ino());
+
+            for (major, minor) in [(8, 1), (259, 3), (0x1234, 0xabcde)] {
+                let identity = FileIdentity::from_dev(libc::makedev(major, minor), 1);
+                assert_eq!((identity.major, identity.minor), (major, minor));
+            }
+        }
+
+        #[test]
+        fn reports_read_only_and_read_write_access() {
+            let read_path = temp_file("read");
+            let write_path = temp_file("write");
+            let _reader = File::open(&read_path).unwrap();
+            let writer = OpenOptions::new().read(true).write(true).open(&write_path).unwrap();
+
+            let usage = own_usage(&read_path);
+            assert_eq!(usage.access_mode(), "read");
+            assert_eq!(usage.fds.len(), 1);
+
+            let usage = own_usage(&write_path);
+            assert_eq!(usage.access_mode(), "read_write");
+            assert_eq!(usage.fds, vec![writer.as_raw_fd() as u32]);
+
+            fs::remove_file(&read_path).unwrap();
+            fs::remove_file(&write_path).unwrap();
+        }
+
+        #[test]
+        fn finds_flock_posix_and_ofd_locks() {
+            let flock_path = temp_file("flock");
+            let posix_path = temp_file("posix");
+            let ofd_path = temp_file("ofd");
+
+            let flocked = File::open(&flock_path).unwrap();
+            assert_eq!(unsafe { libc::flock(flocked.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) }, 0);
+
+            let posix = OpenOptions::new().read(true).write(true).open(&posix_path).unwrap();
+            fcntl_lock(&posix, libc::F_SETLK, 0, 100);
+
+            let ofd = OpenOptions::new().read(true).write(true).open(&ofd_path).unwrap();
+            fcntl_lock(&ofd, libc::F_OFD_SETLK, 10, 0);
+
+            let locks = own_usage(&flock_path).locks;
+            assert_eq!(locks.len(), 1);
+            assert_eq!((locks[0].lock_type.as_str(), locks[0].access.as_str()), ("FLOCK", "WRITE"));
+
+            let locks = own_usage(&posix_path).locks;
+            assert_eq!(locks.len(), 1);
+            assert_eq!(locks[0].lock_type, "POSIX");
+            assert_eq!((locks[0].start, locks[0].end), (0, Some(99)));
+
+            // OFD锁在 /proc/locks 中没有pid，只能通过持有它的描述符找到
+            let locks = own_usage(&ofd_path).locks;
+            assert_eq!(locks.len(), 1);
+            assert_eq!(locks[0].lock_type, "OFDLCK");
+            assert_eq!((locks[0].start, locks[0].end), (10, None));
+
+            for path in [flock_path, posix_path, ofd_path] {
+                fs::remove_file(path).unwrap();
+            }
+        }
+
+        #[test]
+        fn finds_memory_mapped_files() {
+            let path = temp_file("mmap");
+            let file = File::open(&path).unwrap();
+            let address = unsafe {
+                libc::mmap(std::ptr::null_mut(), 4096, libc::PROT_READ, libc::MAP_SHARED, file.as_raw_fd(), 0)
+            };
+            assert_ne!(address, libc::MAP_FAILED);
+            // 关闭描述符后只剩内存映射
+            drop(file);
+
+            let usage = own_usage(&path);
+            assert!(usage.memory_mapped);
+            assert!(usage.fds.is_empty());
+            assert_eq!(usage.access_mode(), "mapped");
+
+            unsafe { libc::munmap(address, 4096) };
+            fs::remove_file(&path).unwrap();
+        }
+
+        #[test]
+        fn finds_files_and_working_directories_under_directory() {
+            let dir = std::env::temp_dir().join(format!("file_lock_dir_{}", std::process::id()));
+            fs::create_dir_all(&dir).unwrap();
+            let file_path = dir.join("data.log");
+            fs::write(&file_path, "log").unwrap();
+            let _file = File::open(&file_path).unwrap();
+
+            let mut child = Command::new("sleep").arg("5").current_dir(&dir).spawn().unwrap();
+
+            let target = FileTarget::new(&dir).unwrap();
+            let users = find_file_users(&target).unwrap();
+
+            let own = &users[&std::process::id()];
+            assert_eq!(own.paths, vec![fs::canonicalize(&file_path).unwrap().to_string_lossy().to_string()]);
+            assert!(!own.cwd);
+
+            let helper = &users[&child.id()];
+            assert!(helper.cwd);
+            assert_eq!(helper.access_mode(), "cwd");
+
+            child.kill().unwrap();
+            child.wait().unwrap();
+            fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+}
@@ -0,0 +1,416 @@
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::commands::port_monitor::PortInfo;
+use crate::monitor::health;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProtocolFingerprint {
+    pub protocol: String,
+    pub version: Option<String>,
+    pub detail: Option<String>,
+}
+
+impl ProtocolFingerprint {
+    fn new(protocol: &str, version: Option<String>, detail: Option<String>) -> Self {
+        ProtocolFingerprint {
+            protocol: protocol.to_string(),
+            version,
+            detail,
+        }
+    }
+}
+
+// 根据探测响应判断协议，部分协议需要再次连接以获取版本号
+type Classifier = fn(SocketAddr, &[u8]) -> Option<ProtocolFingerprint>;
+
+const PROBE_TIMEOUT: Duration = Duration::from_millis(500);
+
+const REDIS_PING: &[u8] = b"*1\r\n$4\r\nPING\r\n";
+const REDIS_INFO: &[u8] = b"*2\r\n$4\r\nINFO\r\n$6\r\nserver\r\n";
+const POSTGRES_SSL_REQUEST: &[u8] = &[0x00, 0x00, 0x00, 0x08, 0x04, 0xd2, 0x16, 0x2f];
+const AMQP_HEADER: &[u8] = b"AMQP\x00\x00\x09\x01";
+// 连接前言之后紧跟一个空的SETTINGS帧
+const HTTP2_PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n\x00\x00\x00\x04\x00\x00\x00\x00\x00";
+// MQTT 3.1.1 CONNECT，客户端ID为 "systoolkit"
+const MQTT_CONNECT: &[u8] = &[
+    0x10, 0x16, 0x00, 0x04, b'M', b'Q', b'T', b'T', 0x04, 0x02, 0x00, 0x3c,
+    0x00, 0x0a, b's', b'y', b's', b't', b'o', b'o', b'l', b'k', b'i', b't',
+];
+
+/// 对未识别出项目的TCP监听端口做协议识别，结果写入 `PortInfo.fingerprint`
+///
+/// 每个端口最多需要数次串行的连接，会阻塞较长时间，应在独立线程中调用。
+pub fn fingerprint_ports(ports: &mut [PortInfo]) {
+    let targets: Vec<_> = ports
+        .iter()
+        .map(|port| {
+            if port.protocol == "TCP" && port.project.is_none() {
+                health::probe_target(&port.local_address, port.port)
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    let results = health::run_bounded(targets, health::MAX_CONCURRENT_PROBES, |target| target.and_then(fingerprint));
+
+    for (port, fingerprint) in ports.iter_mut().zip(results) {
+        port.fingerprint = fingerprint.flatten();
+    }
+}
+
+/// 依次尝试各协议的握手，每次探测使用新的连接
+///
+/// 先等待服务端主动发送的欢迎信息（SSH、MySQL），再按TLS、Redis、PostgreSQL、
+/// HTTP/2、AMQP、MQTT、HTTP/1.1的顺序发送探测报文。
+pub fn fingerprint(addr: SocketAddr) -> Option<ProtocolFingerprint> {
+    if let Some(banner) = exchange(addr, None) {
+        if let Some(result) = classify_banner(&banner) {
+            return Some(result);
+        }
+    }
+
+    let client_hello = tls_client_hello();
+    let probes: [(&[u8], Classifier); 6] = [
+        (&client_hello, classify_tls),
+        (REDIS_PING, classify_redis),
+        (POSTGRES_SSL_REQUEST, classify_postgres),
+        (HTTP2_PREFACE, classify_http2),
+        (AMQP_HEADER, classify_amqp),
+        (MQTT_CONNECT, classify_mqtt),
+    ];
+
+    for (request, classify) in probes.iter() {
+        if let Some(response) = exchange(addr, Some(request)) {
+            // 明文HTTP服务会对任何无法识别的请求返回400
+            if let Some(result) = classify_http(&response).or_else(|| classify(addr, &response)) {
+                return Some(result);
+            }
+        }
+    }
+
+    let request = format!("GET / HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n", addr);
+    exchange(addr, Some(request.as_bytes())).and_then(|response| classify_http(&response))
+}
+
+// 建立连接并可选地发送请求，返回服务端的第一段响应
+fn exchange(addr: SocketAddr, request: Option<&[u8]>) -> Option<Vec<u8>> {
+    let mut stream = TcpStream::connect_timeout(&addr, PROBE_TIMEOUT).ok()?;
+    stream.set_read_timeout(Some(PROBE_TIMEOUT)).ok()?;
+    stream.set_write_timeout(Some(PROBE_TIMEOUT)).ok()?;
+
+    if let Some(request) = request {
+        stream.write_all(request).ok()?;
+    }
+
+    read_response(&mut stream)
+}
+
+fn read_response(stream: &mut TcpStream) -> Option<Vec<u8>> {
+    let mut buf = [0u8; 4096];
+    let n = stream.read(&mut buf).ok()?;
+
+    if n == 0 {
+        None
+    } else {
+        Some(buf[..n].to_vec())
+    }
+}
+
+fn classify_banner(banner: &[u8]) -> Option<ProtocolFingerprint> {
+    // SSH-2.0-OpenSSH_9.6p1 Ubuntu-3ubuntu13
+    if let Some(rest) = banner.strip_prefix(b"SSH-") {
+        let line = String::from_utf8_lossy(rest);
+        let line = line.lines().next().unwrap_or("");
+        let (version, software) = line.split_once('-').unwrap_or((line, ""));
+
+        return Some(ProtocolFingerprint::new(
+            "SSH",
+            Some(version.to_string()),
+            Some(software.to_string()).filter(|s| !s.is_empty()),
+        ));
+    }
+
+    // MySQL握手包: 3字节长度 + 序号0 + 协议版本10 + 以NUL结尾的服务器版本
+    if banner.len() > 5 && banner[3] == 0 && banner[4] == 0x0a {
+        let version_end = banner[5..].iter().position(|&b| b == 0)?;
+        let version = String::from_utf8_lossy(&banner[5..5 + version_end]).to_string();
+        let protocol = if version.contains("MariaDB") { "MariaDB" } else { "MySQL" };
+
+        return Some(ProtocolFingerprint::new(protocol, Some(version), None));
+    }
+
+    None
+}
+
+fn classify_http(response: &[u8]) -> Option<ProtocolFingerprint> {
+    let text = String::from_utf8_lossy(response);
+    let status_line = text.lines().next()?;
+    let version = status_line.strip_prefix("HTTP/")?.split_whitespace().next()?;
+
+    let server = text
+        .lines()
+        .skip(1)
+        .take_while(|line| !line.is_empty())
+        .find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            if name.eq_ignore_ascii_case("server") {
+                Some(value.trim().to_string())
+            } else {
+                None
+            }
+        });
+
+    Some(ProtocolFingerprint::new("HTTP", Some(version.to_string()), server))
+}
+
+fn classify_tls(_addr: SocketAddr, response: &[u8]) -> Option<ProtocolFingerprint> {
+    // 记录层: 类型(0x16握手 / 0x15告警) + 版本0x03xx
+    if response.len() < 5 || response[1] != 0x03 {
+        return None;
+    }
+
+    let detail = match response[0] {
+        0x16 => "ServerHello",
+        0x15 => "Alert",
+        _ => return None,
+    };
+
+    Some(ProtocolFingerprint::new("TLS", None, Some(detail.to_string())))
+}
+
+fn classify_redis(addr: SocketAddr, response: &[u8]) -> Option<ProtocolFingerprint> {
+    if response.starts_with(b"-NOAUTH") || response.starts_with(b"-DENIED") {
+        return Some(ProtocolFingerprint::new("Redis", None, Some("需要认证".to_string())));
+    }
+
+    if !response.starts_with(b"+PONG") {
+        return None;
+    }
+
+    // INFO server 中的 redis_version 字段
+    let version = exchange(addr, Some(REDIS_INFO)).and_then(|info| {
+        String::from_utf8_lossy(&info)
+            .lines()
+            .find_map(|line| line.strip_prefix("redis_version:"))
+            .map(|v| v.trim().to_string())
+    });
+
+    Some(ProtocolFingerprint::new("Redis", version, None))
+}
+
+fn classify_postgres(_addr: SocketAddr, response: &[u8]) -> Option<ProtocolFingerprint> {
+    // SSLRequest只会得到单字节的 'S' 或 'N'
+    match response {
+        [b'S'] => Some(ProtocolFingerprint::new("PostgreSQL", None, Some("支持SSL".to_string()))),
+        [b'N'] => Some(ProtocolFingerprint::new("PostgreSQL", None, Some("未启用SSL".to_string()))),
+        _ => None,
+    }
+}
+
+fn classify_http2(_addr: SocketAddr, response: &[u8]) -> Option<ProtocolFingerprint> {
+    // 服务端的第一个帧必须是SETTINGS帧: 3字节长度 + 类型0x04
+    if response.len() >= 9 && response[3] == 0x04 {
+        Some(ProtocolFingerprint::new("HTTP", Some("2".to_string()), Some("h2c".to_string())))
+    } else {
+        None
+    }
+}
+
+fn classify_amqp(_addr: SocketAddr, response: &[u8]) -> Option<ProtocolFingerprint> {
+    // 版本不匹配时服务端回送自己支持的协议头
+    if response.len() >= 8 && response.starts_with(b"AMQP") {
+        let version = format!("{}-{}-{}", response[5], response[6], response[7]);
+        return Some(ProtocolFingerprint::new("AMQP", Some(version), None));
+    }
+
+    // Connection.Start方法帧: 类型1，class 10，method 10，随后是版本号
+    if response.len() >= 13 && response[0] == 0x01 && response[7..11] == [0x00, 0x0a, 0x00, 0x0a] {
+        let version = format!("{}-{}", response[11], response[12]);
+        return Some(ProtocolFingerprint::new("AMQP", Some(version), None));
+    }
+
+    None
+}
+
+fn classify_mqtt(_addr: SocketAddr, response: &[u8]) -> Option<ProtocolFingerprint> {
+    // CONNACK: 0x20 0x02 标志 返回码
+    if response.len() >= 4 && response[0] == 0x20 && response[1] == 0x02 {
+        let detail = if response[3] == 0 { "连接已接受" } else { "连接被拒绝" };
+        Some(ProtocolFingerprint::new("MQTT", Some("3.1.1".to_string()), Some(detail.to_string())))
+    } else {
+        None
+    }
+}
+
+// 最小的TLS 1.2 ClientHello，足以让TLS服务端回应ServerHello或告警
+fn tls_client_hello() -> Vec<u8> {
+    let cipher_suites: [u8; 22] = [
+        0x13, 0x01, 0x13, 0x02, 0x13, 0x03, 0xc0, 0x2b, 0xc0, 0x2f, 0xc0, 0x2c,
+        0xc0, 0x30, 0x00, 0x9c, 0x00, 0x9d, 0x00, 0x2f, 0x00, 0x35,
+    ];
+
+    let mut hello = vec![0x03, 0x03];
+    hello.extend_from_slice(&[0u8; 32]); // random
+    hello.push(0x00); // session id
+    hello.extend_from_slice(&(cipher_suites.len() as u16).to_be_bytes());
+    hello.extend_from_slice(&cipher_suites);
+    hello.extend_from_slice(&[0x01, 0x00]); // 仅支持null压缩
+    hello.extend_from_slice(&[0x00, 0x00]); // 无扩展
+
+    let mut handshake = vec![0x01];
+    handshake.extend_from_slice(&(hello.len() as u32).to_be_bytes()[1..]);
+    handshake.extend_from_slice(&hello);
+
+    let mut record = vec![0x16, 0x03, 0x01];
+    record.extend_from_slice(&(handshake.len() as u16).to_be_bytes());
+    record.extend_from_slice(&handshake);
+    record
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    // 连接后先发送 `greeting`，收到请求时按顺序返回 `replies`
+    fn serve(greeting: &'static [u8], replies: Vec<&'static [u8]>) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            for reply in replies {
+                let (mut stream, _) = match listener.accept() {
+                    Ok(conn) => conn,
+                    Err(_) => return,
+                };
+                let _ = stream.write_all(greeting);
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf);
+                let _ = stream.write_all(reply);
+            }
+        });
+
+        addr
+    }
+
+    fn closed_addr() -> SocketAddr {
+        TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap()
+    }
+
+    #[test]
+    fn classifies_ssh_banner() {
+        let result = classify_banner(b"SSH-2.0-OpenSSH_9.6p1 Ubuntu-3ubuntu13\r\n").unwrap();
+        assert_eq!(result.protocol, "SSH");
+        assert_eq!(result.version.as_deref(), Some("2.0"));
+        assert_eq!(result.detail.as_deref(), Some("OpenSSH_9.6p1 Ubuntu-3ubuntu13"));
+    }
+
+    #[test]
+    fn classifies_mysql_handshake() {
+        let mut banner = vec![0x4a, 0x00, 0x00, 0x00, 0x0a];
+        banner.extend_from_slice(b"8.0.36\0");
+        banner.extend_from_slice(&[0x08, 0x00, 0x00, 0x00]);
+        let result = classify_banner(&banner).unwrap();
+        assert_eq!(result.protocol, "MySQL");
+        assert_eq!(result.version.as_deref(), Some("8.0.36"));
+
+        let mut banner = vec![0x5b, 0x00, 0x00, 0x00, 0x0a];
+        banner.extend_from_slice(b"5.5.5-10.11.6-MariaDB\0");
+        assert_eq!(classify_banner(&banner).unwrap().protocol, "MariaDB");
+
+        assert!(classify_banner(b"220 smtp.example.com ESMTP\r\n").is_none());
+    }
+
+    #[test]
+    fn classifies_http_response() {
+        let response = b"HTTP/1.1 400 Bad Request\r\nServer: nginx/1.24.0\r\nContent-Length: 0\r\n\r\n";
+        let result = classify_http(response).unwrap();
+        assert_eq!(result.protocol, "HTTP");
+        assert_eq!(result.version.as_deref(), Some("1.1"));
+        assert_eq!(result.detail.as_deref(), Some("nginx/1.24.0"));
+
+        let result = classify_http(b"HTTP/1.0 200 OK\r\n\r\nServer: body").unwrap();
+        assert_eq!(result.detail, None);
+
+        assert!(classify_http(b"+PONG\r\n").is_none());
+    }
+
+    #[test]
+    fn classifies_redis_replies() {
+        let result = classify_redis(closed_addr(), b"-NOAUTH Authentication required.\r\n").unwrap();
+        assert_eq!(result.protocol, "Redis");
+        assert_eq!(result.detail.as_deref(), Some("需要认证"));
+
+        // INFO请求失败时只识别协议
+        let result = classify_redis(closed_addr(), b"+PONG\r\n").unwrap();
+        assert_eq!(result.protocol, "Redis");
+        assert_eq!(result.version, None);
+
+        let addr = serve(b"", vec![b"$60\r\n# Server\r\nredis_version:7.2.4\r\nredis_mode:standalone\r\n\r\n"]);
+        let result = classify_redis(addr, b"+PONG\r\n").unwrap();
+        assert_eq!(result.version.as_deref(), Some("7.2.4"));
+
+        assert!(classify_redis(closed_addr(), b"-ERR unknown command\r\n").is_none());
+    }
+
+    #[test]
+    fn classifies_postgres_ssl_reply() {
+        let result = classify_postgres(closed_addr(), b"S").unwrap();
+        assert_eq!(result.protocol, "PostgreSQL");
+        assert_eq!(result.detail.as_deref(), Some("支持SSL"));
+        assert_eq!(classify_postgres(closed_addr(), b"N").unwrap().detail.as_deref(), Some("未启用SSL"));
+        assert!(classify_postgres(closed_addr(), b"SN").is_none());
+    }
+
+    #[test]
+    fn classifies_tls_records() {
+        let server_hello = [0x16, 0x03, 0x03, 0x00, 0x5a, 0x02, 0x00, 0x00, 0x56];
+        let result = classify_tls(closed_addr(), &server_hello).unwrap();
+        assert_eq!(result.protocol, "TLS");
+        assert_eq!(result.detail.as_deref(), Some("ServerHello"));
+
+        let alert = [0x15, 0x03, 0x01, 0x00, 0x02, 0x02, 0x28];
+        assert_eq!(classify_tls(closed_addr(), &alert).unwrap().detail.as_deref(), Some("Alert"));
+
+        assert!(classify_tls(closed_addr(), b"HTTP/1.1 400").is_none());
+        assert!(classify_tls(closed_addr(), &[0x16, 0x03]).is_none());
+    }
+
+    #[test]
+    fn classifies_other_binary_protocols() {
+        let settings = [0x00, 0x00, 0x06, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03];
+        assert_eq!(classify_http2(closed_addr(), &settings).unwrap().version.as_deref(), Some("2"));
+
+        let result = classify_amqp(closed_addr(), b"AMQP\x00\x00\x09\x01").unwrap();
+        assert_eq!(result.version.as_deref(), Some("0-9-1"));
+
+        let result = classify_mqtt(closed_addr(), &[0x20, 0x02, 0x00, 0x05]).unwrap();
+        assert_eq!(result.detail.as_deref(), Some("连接被拒绝"));
+    }
+
+    #[test]
+    fn client_hello_is_well_formed() {
+        let hello = tls_client_hello();
+        assert_eq!(&hello[..3], &[0x16, 0x03, 0x01]);
+        assert_eq!(u16::from_be_bytes([hello[3], hello[4]]) as usize, hello.len() - 5);
+        assert_eq!(hello[5], 0x01);
+    }
+
+    #[test]
+    fn fingerprints_stand_in_servers() {
+        let addr = serve(b"SSH-2.0-dropbear_2022.83\r\n", vec![b""]);
+        let result = fingerprint(addr).unwrap();
+        assert_eq!(result.protocol, "SSH");
+        assert_eq!(result.detail.as_deref(), Some("dropbear_2022.83"));
+
+        // 没有欢迎信息，TLS探测得到告警
+        let addr = serve(b"", vec![b"", &[0x15, 0x03, 0x03, 0x00, 0x02, 0x02, 0x46]]);
+        assert_eq!(fingerprint(addr).unwrap().protocol, "TLS");
+
+        assert!(fingerprint(closed_addr()).is_none());
+    }
+}
@@ -0,0 +1,322 @@
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use crate::commands::port_monitor::PortInfo;
+use crate::rules::project_detector::{self, HealthProbe, ProbeKind};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HealthStatus {
+    pub probe: String,
+    pub healthy: bool,
+    pub latency_ms: u64,
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+}
+
+// 同时探测的端口数上限
+pub const MAX_CONCURRENT_PROBES: usize = 16;
+
+/// 并发检查所有TCP监听端口，结果写入 `PortInfo.health`
+///
+/// 会阻塞到全部探测完成或超时，应在独立线程中调用。
+pub fn probe_ports(ports: &mut [PortInfo]) {
+    let jobs: Vec<_> = ports
+        .iter()
+        .map(|port| {
+            let target = if port.protocol == "TCP" {
+                probe_target(&port.local_address, port.port)
+            } else {
+                None
+            };
+            let probe = project_detector::get_health_probe(
+                port.project.as_ref().map(|p| p.project_type.as_str()),
+            );
+
+            (target, probe)
+        })
+        .collect();
+
+    let results = run_bounded(jobs, MAX_CONCURRENT_PROBES, |(target, probe)| {
+        target.map(|addr| run_probe(addr, &probe))
+    });
+
+    for (port, health) in ports.iter_mut().zip(results) {
+        port.health = health.flatten();
+    }
+}
+
+/// 用至多 `workers` 个线程依次处理各项任务，结果按输入顺序返回，任务panic时对应结果为None
+pub fn run_bounded<T, R, F>(items: Vec<T>, workers: usize, job: F) -> Vec<Option<R>>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> R + Send + Sync + 'static,
+{
+    let count = items.len();
+    let queue = Arc::new(Mutex::new(items.into_iter().enumerate().collect::<VecDeque<_>>()));
+    let job = Arc::new(job);
+    let (result_tx, result_rx) = mpsc::channel();
+
+    let handles: Vec<_> = (0..workers.max(1).min(count))
+        .map(|_| {
+            let queue = queue.clone();
+            let job = job.clone();
+            let result_tx = result_tx.clone();
+
+            thread::spawn(move || loop {
+                let next = queue.lock().unwrap().pop_front();
+                match next {
+                    Some((index, item)) => {
+                        let _ = result_tx.send((index, job(item)));
+                    }
+                    None => break,
+                }
+            })
+        })
+        .collect();
+    drop(result_tx);
+
+    let mut results: Vec<Option<R>> = (0..count).map(|_| None).collect();
+    for (index, result) in result_rx {
+        results[index] = Some(result);
+    }
+    for handle in handles {
+        let _ = handle.join();
+    }
+
+    results
+}
+
+/// 监听在通配地址上的服务通过同协议族的回环地址访问
+pub fn probe_target(local_address: &str, port: u16) -> Option<SocketAddr> {
+    let ip = match local_address.parse::<IpAddr>().ok()? {
+        IpAddr::V4(ip) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V6(ip) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        ip => ip,
+    };
+
+    Some(SocketAddr::new(ip, port))
+}
+
+pub fn run_probe(addr: SocketAddr, probe: &HealthProbe) -> HealthStatus {
+    let timeout = Duration::from_millis(probe.timeout_ms);
+
+    match probe.kind {
+        ProbeKind::Tcp => probe_tcp(addr, timeout),
+        ProbeKind::Http => probe_http(addr, probe.path.as_deref().unwrap_or("/"), timeout),
+    }
+}
+
+fn probe_tcp(addr: SocketAddr, timeout: Duration) -> HealthStatus {
+    let started = Instant::now();
+    let result = TcpStream::connect_timeout(&addr, timeout);
+
+    HealthStatus {
+        probe: "TCP".to_string(),
+        healthy: result.is_ok(),
+        latency_ms: started.elapsed().as_millis() as u64,
+        status_code: None,
+        error: result.err().map(|e| e.to_string()),
+    }
+}
+
+// 服务能正常返回5xx以下的状态码即视为健康，404等说明服务本身在正常工作
+fn probe_http(addr: SocketAddr, path: &str, timeout: Duration) -> HealthStatus {
+    let started = Instant::now();
+    let result = http_get_status(addr, path, timeout);
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    match result {
+        Ok(status_code) => HealthStatus {
+            probe: "HTTP".to_string(),
+            healthy: status_code < 500,
+            latency_ms,
+            status_code: Some(status_code),
+            error: None,
+        },
+        Err(e) => HealthStatus {
+            probe: "HTTP".to_string(),
+            healthy: false,
+            latency_ms,
+            status_code: None,
+            error: Some(e),
+        },
+    }
+}
+
+fn http_get_status(addr: SocketAddr, path: &str, timeout: Duration) -> Result<u16, String> {
+    let mut stream = TcpStream::connect_timeout(&addr, timeout).map_err(|e| e.to_string())?;
+    stream.set_read_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+    stream.set_write_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: SysToolkit\r\nAccept: */*\r\nConnection: close\r\n\r\n",
+        path, addr
+    );
+    stream.write_all(request.as_bytes()).map_err(|e| e.to_string())?;
+
+    // 只需要读到状态行
+    let mut response = Vec::new();
+    let mut buf = [0u8; 512];
+    while !response.windows(2).any(|w| w == b"\r\n") && response.len() < 4096 {
+        let n = stream.read(&mut buf).map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        response.extend_from_slice(&buf[..n]);
+    }
+
+    parse_status_line(&String::from_utf8_lossy(&response))
+        .ok_or_else(|| "响应不是有效的HTTP报文".to_string())
+}
+
+// 状态行格式: HTTP/1.1 200 OK
+fn parse_status_line(response: &str) -> Option<u16> {
+    let line = response.lines().next()?;
+    let mut parts = line.split_whitespace();
+
+    if !parts.next()?.starts_with("HTTP/") {
+        return None;
+    }
+
+    parts.next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    // 在回环地址上启动只应答一次的HTTP服务，返回其地址
+    fn serve_once(response: &'static str, delay: Duration) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf);
+                thread::sleep(delay);
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        addr
+    }
+
+    // 绑定后立即释放，得到一个当前无人监听的端口
+    fn closed_addr() -> SocketAddr {
+        TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap()
+    }
+
+    #[test]
+    fn http_probe_reports_status_and_latency() {
+        let addr = serve_once("HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n", Duration::from_millis(50));
+        let status = run_probe(addr, &HealthProbe::http("/health"));
+
+        assert_eq!(status.probe, "HTTP");
+        assert!(status.healthy);
+        assert_eq!(status.status_code, Some(204));
+        assert!(status.error.is_none());
+        assert!(status.latency_ms >= 50, "latency {}", status.latency_ms);
+        assert!(status.latency_ms < 2000);
+    }
+
+    #[test]
+    fn http_probe_treats_4xx_as_healthy_and_5xx_as_unhealthy() {
+        let addr = serve_once("HTTP/1.1 404 Not Found\r\n\r\n", Duration::ZERO);
+        let status = probe_http(addr, "/", Duration::from_secs(1));
+        assert!(status.healthy);
+        assert_eq!(status.status_code, Some(404));
+
+        let addr = serve_once("HTTP/1.0 503 Service Unavailable\r\n\r\n", Duration::ZERO);
+        let status = probe_http(addr, "/", Duration::from_secs(1));
+        assert!(!status.healthy);
+        assert_eq!(status.status_code, Some(503));
+    }
+
+    #[test]
+    fn http_probe_rejects_non_http_response() {
+        let addr = serve_once("SSH-2.0-OpenSSH_9.6\r\n", Duration::ZERO);
+        let status = probe_http(addr, "/", Duration::from_secs(1));
+
+        assert!(!status.healthy);
+        assert_eq!(status.status_code, None);
+        assert!(status.error.is_some());
+    }
+
+    #[test]
+    fn tcp_probe_connects_to_listener() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let status = run_probe(listener.local_addr().unwrap(), &HealthProbe::tcp());
+
+        assert_eq!(status.probe, "TCP");
+        assert!(status.healthy);
+        assert!(status.error.is_none());
+    }
+
+    #[test]
+    fn closed_port_is_unhealthy() {
+        let addr = closed_addr();
+
+        let status = run_probe(addr, &HealthProbe::tcp());
+        assert!(!status.healthy);
+        assert!(status.error.is_some());
+
+        let status = run_probe(addr, &HealthProbe::http("/"));
+        assert!(!status.healthy);
+        assert_eq!(status.status_code, None);
+    }
+
+    #[test]
+    fn slow_server_times_out() {
+        let addr = serve_once("HTTP/1.1 200 OK\r\n\r\n", Duration::from_secs(3));
+        let started = Instant::now();
+        let status = probe_http(addr, "/", Duration::from_millis(200));
+
+        assert!(!status.healthy);
+        assert!(status.error.is_some());
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn wildcard_addresses_probe_loopback() {
+        assert_eq!(probe_target("0.0.0.0", 80), Some("127.0.0.1:80".parse().unwrap()));
+        assert_eq!(probe_target("::", 80), Some("[::1]:80".parse().unwrap()));
+        assert_eq!(probe_target("192.168.1.5", 80), Some("192.168.1.5:80".parse().unwrap()));
+        assert_eq!(probe_target("*", 80), None);
+    }
+
+    #[test]
+    fn parses_status_lines() {
+        assert_eq!(parse_status_line("HTTP/1.1 200 OK\r\n"), Some(200));
+        assert_eq!(parse_status_line("HTTP/2 301\r\n"), Some(301));
+        assert_eq!(parse_status_line("+PONG\r\n"), None);
+        assert_eq!(parse_status_line(""), None);
+    }
+
+    #[test]
+    fn run_bounded_keeps_order_and_limits_threads() {
+        let active = Arc::new(Mutex::new((0usize, 0usize)));
+        let counter = active.clone();
+
+        let results = run_bounded((0..20).collect(), 4, move |n: u32| {
+            {
+                let mut state = counter.lock().unwrap();
+                state.0 += 1;
+                state.1 = state.1.max(state.0);
+            }
+            thread::sleep(Duration::from_millis(10));
+            counter.lock().unwrap().0 -= 1;
+            n * 2
+        });
+
+        assert_eq!(results, (0..20).map(|n| Some(n * 2)).collect::<Vec<_>>());
+        assert!(active.lock().unwrap().1 <= 4);
+        assert!(run_bounded(Vec::<u32>::new(), 4, |n| n).is_empty());
+    }
+}
@@ -1,3 +1,21 @@
 pub mod port;
 pub mod file;
-pub mod docker;
\ No newline at end of file
+pub mod file_lock;
+pub mod docker;
+pub mod proc_net;
+pub mod netstat;
+pub mod ss;
+pub mod port_watcher;
+pub mod port_history;
+pub mod health;
+pub mod fingerprint;
+pub mod process_tree;
+pub mod process_sampler;
+pub mod process_query;
+pub mod process_terminator;
+pub mod process_control;
+pub mod process_detail;
+pub mod process_history;
+pub mod process_restarter;
+pub mod audit_log;
+pub mod model;
\ No newline at end of file
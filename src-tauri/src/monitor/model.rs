@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+
+/// 操作风险等级，按从低到高排序
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RiskLevel {
+    None,
+    Low,
+    Medium,
+    High,
+}
+
+/// 可由 `execute_action` 执行的操作及其目标
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum ActionKind {
+    /// 请求进程优雅退出，超时后强制终止
+    StopProcess { pid: u32 },
+    /// 跳过优雅退出直接强制终止
+    ForceKillProcess { pid: u32 },
+    KillProcessTree { pid: u32 },
+    RestartProcess { pid: u32 },
+    ShowProcessDetail { pid: u32 },
+    /// 读取进程正在写入的日志文件末尾
+    ShowProcessLogs { pid: u32 },
+    StopContainer { container_id: String },
+    RestartContainer { container_id: String },
+    ShowContainer { container_id: String },
+    ShowContainerLogs { container_id: String },
+    /// 容器ID未知时按映射的宿主机端口查找容器
+    StopContainerOnPort { port: u16 },
+    ShowContainerOnPort { port: u16 },
+    OpenBrowser { url: String },
+    /// 打开目录，路径为文件时打开其所在目录，不会直接打开文件
+    OpenFolder { path: String },
+    OpenTaskManager,
+    /// 以下为提示性建议，不执行任何操作
+    UseAlternativePort { port: u16 },
+    WaitForRelease { port: u16 },
+    /// 需要用户手动完成的操作，如保存文档后关闭应用
+    Manual,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ActionSuggestion {
+    pub action: String,
+    pub description: String,
+    pub risk_level: RiskLevel,
+    /// 可通过 execute_action 执行的操作
+    pub kind: ActionKind,
+}
+
+impl ActionSuggestion {
+    pub fn new(action: &str, description: String, risk_level: RiskLevel, kind: ActionKind) -> Self {
+        ActionSuggestion {
+            action: action.to_string(),
+            description,
+            risk_level,
+            kind,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileOccupancy {
+    pub file_path: String,
+    pub file_name: String,
+    /// 检测的是目录时，结果包含其下全部文件的占用和以其为工作目录的进程
+    pub is_directory: bool,
+    pub occupied_by: Vec<FileProcessInfo>,
+    pub reason: String,
+    pub impact: String,
+    pub solutions: Vec<Solution>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    pub friendly_name: String,
+    pub exe_path: Option<String>,
+    /// 访问方式：read、write、read_write；仅映射到内存时为 mapped，
+    /// 仅以目录为工作目录时为 cwd，只持有锁时为 none
+    pub access_mode: String,
+    /// 打开该文件的文件描述符
+    pub fds: Vec<u32>,
+    /// 被占用的文件路径，检测目录时为其下的各个文件
+    pub paths: Vec<String>,
+    /// 文件是否被映射到进程内存，如已加载的动态库
+    pub memory_mapped: bool,
+    /// 进程的工作目录位于检测的目录下
+    pub uses_as_cwd: bool,
+    pub locks: Vec<FileLock>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileLock {
+    /// POSIX、FLOCK、OFDLCK 或 LEASE
+    pub lock_type: String,
+    pub mandatory: bool,
+    /// READ 或 WRITE
+    pub access: String,
+    pub start: u64,
+    /// 锁定区间的结束偏移，None表示到文件末尾
+    pub end: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Solution {
+    pub action: String,
+    pub description: String,
+    pub risk_level: RiskLevel,
+    /// 可通过 execute_action 执行的操作
+    pub kind: ActionKind,
+    pub command: Option<String>,
+}
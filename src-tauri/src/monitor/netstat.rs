@@ -0,0 +1,203 @@
+use std::error::Error;
+use std::process::Command;
+use crate::monitor::port::{self, RawSocket, SocketBackend};
+
+/// 通过netstat命令采集套接字，Windows使用 `netstat -ano`，其他平台使用net-tools的 `netstat -tuanp`
+pub struct NetstatBackend;
+
+impl SocketBackend for NetstatBackend {
+    fn name(&self) -> &'static str {
+        "netstat"
+    }
+
+    fn sockets(&self) -> Result<Vec<RawSocket>, Box<dyn Error>> {
+        let output = if cfg!(target_os = "windows") {
+            Command::new("netstat")
+                .args(["-ano"])
+                .output()?
+        } else {
+            Command::new("netstat")
+                .args(["-tuanp"])
+                .output()?
+        };
+
+        if !output.status.success() {
+            return Err(format!("netstat执行失败: {}", String::from_utf8_lossy(&output.stderr)).into());
+        }
+
+        Ok(parse_netstat_output(&String::from_utf8_lossy(&output.stdout)))
+    }
+}
+
+/// 解析netstat输出，同时支持Windows和Linux net-tools两种格式，表头等无关行会被忽略
+pub fn parse_netstat_output(output: &str) -> Vec<RawSocket> {
+    output.lines().filter_map(parse_netstat_line).collect()
+}
+
+fn parse_netstat_line(line: &str) -> Option<RawSocket> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+
+    match *parts.first()? {
+        "TCP" | "TCPv6" | "UDP" | "UDPv6" => parse_windows_line(&parts),
+        "tcp" | "tcp6" | "udp" | "udp6" => parse_net_tools_line(&parts),
+        _ => None,
+    }
+}
+
+// Windows netstat格式:
+//   TCP 0.0.0.0:3000 0.0.0.0:0 LISTENING 1234
+//   UDP [::]:5353 *:* 5678
+fn parse_windows_line(parts: &[&str]) -> Option<RawSocket> {
+    let (protocol, state, pid_field) = match parts[0] {
+        "TCP" | "TCPv6" if parts.len() >= 5 => ("TCP", parts[3], parts[4]),
+        "UDP" | "UDPv6" if parts.len() >= 4 => ("UDP", "LISTENING", parts[3]),
+        _ => return None,
+    };
+
+    build_socket(protocol, parts[1], parts[2], state, pid_field.parse().ok())
+}
+
+// Linux net-tools格式:
+//   tcp   0 0 127.0.0.1:5432 0.0.0.0:* LISTEN 1234/postgres
+//   tcp6  0 0 :::80          :::*      LISTEN -
+//   udp   0 0 0.0.0.0:68     0.0.0.0:*        812/dhclient
+//   tcp6  0 0 :::80          :::*      LISTEN 1234/nginx: master
+fn parse_net_tools_line(parts: &[&str]) -> Option<RawSocket> {
+    let protocol = if parts[0].starts_with("tcp") { "TCP" } else { "UDP" };
+
+    // 未连接的UDP套接字没有状态列；程序名可能包含空格，只取PID所在的第一段
+    let (state, program) = match parts.get(5..)? {
+        [program, ..] if protocol == "UDP" && is_program_field(program) => ("LISTENING", *program),
+        [state, program, ..] => (*state, *program),
+        _ => return None,
+    };
+
+    // 没有权限查看的进程显示为 "-"
+    let pid = program.split('/').next().and_then(|pid| pid.parse().ok());
+
+    build_socket(protocol, parts[3], parts[4], state, pid)
+}
+
+// 程序列为 "PID/程序名" 或 "-"
+fn is_program_field(field: &str) -> bool {
+    field == "-" || field.starts_with(|c: char| c.is_ascii_digit())
+}
+
+fn build_socket(
+    protocol: &str,
+    local: &str,
+    remote: &str,
+    state: &str,
+    pid: Option<u32>,
+) -> Option<RawSocket> {
+    Some(RawSocket {
+        protocol: protocol.to_string(),
+        local_address: port::extract_host_from_addr(local)?,
+        local_port: port::extract_port_from_addr(local)?,
+        // UDP的远端地址为 "*:*" 或 "0.0.0.0:*"
+        remote_address: port::extract_host_from_addr(remote).unwrap_or_else(|| remote.to_string()),
+        remote_port: port::extract_port_from_addr(remote).unwrap_or(0),
+        state: port::normalize_state(state),
+        pids: pid.into_iter().collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Windows: netstat -ano
+    const WINDOWS: &str = "\r
+Active Connections\r
+\r
+  Proto  Local Address          Foreign Address        State           PID\r
+  TCP    0.0.0.0:135            0.0.0.0:0              LISTENING       1040\r
+  TCP    0.0.0.0:3000           0.0.0.0:0              LISTENING       8612\r
+  TCP    127.0.0.1:3000         127.0.0.1:52144        ESTABLISHED     8612\r
+  TCP    127.0.0.1:52100        127.0.0.1:3000         TIME_WAIT       0\r
+  TCP    [::]:3000              [::]:0                 LISTENING       8612\r
+  TCP    [fe80::1%4]:139        [::]:0                 LISTENING       4\r
+  UDP    0.0.0.0:5353           *:*                                    2216\r
+  UDP    [::]:5353              *:*                                    2216\r
+";
+
+    // Linux net-tools: netstat -tulpn
+    const NET_TOOLS: &str = "\
+Active Internet connections (only servers)
+Proto Recv-Q Send-Q Local Address           Foreign Address         State       PID/Program name
+tcp        0      0 127.0.0.1:5432          0.0.0.0:*               LISTEN      901/postgres
+tcp        0      0 0.0.0.0:22              0.0.0.0:*               LISTEN      -
+tcp6       0      0 :::80                   :::*                    LISTEN      1234/nginx: master
+tcp        0      0 10.0.0.5:22             10.0.0.9:51022          ESTABLISHED 3120/sshd: alice
+udp        0      0 0.0.0.0:68              0.0.0.0:*                           812/dhclient
+udp6       0      0 :::546                  :::*                                -
+";
+
+    #[test]
+    fn parses_windows_output() {
+        let sockets = parse_netstat_output(WINDOWS);
+        assert_eq!(sockets.len(), 8);
+
+        let node = &sockets[1];
+        assert_eq!(node.protocol, "TCP");
+        assert_eq!(node.local_address, "0.0.0.0");
+        assert_eq!(node.local_port, 3000);
+        assert_eq!(node.state, "LISTENING");
+        assert_eq!(node.pids, vec![8612]);
+
+        assert_eq!(sockets[2].state, "ESTABLISHED");
+        assert_eq!(sockets[2].remote_port, 52144);
+        assert_eq!(sockets[3].state, "TIME_WAIT");
+        assert_eq!(sockets[3].pids, vec![0]);
+
+        assert_eq!(sockets[4].local_address, "::");
+        assert_eq!(sockets[5].local_address, "fe80::1%4");
+        assert_eq!(sockets[5].local_port, 139);
+
+        let mdns = &sockets[7];
+        assert_eq!(mdns.protocol, "UDP");
+        assert_eq!(mdns.local_address, "::");
+        assert_eq!(mdns.state, "LISTENING");
+        assert_eq!(mdns.remote_address, "*");
+        assert_eq!(mdns.pids, vec![2216]);
+    }
+
+    #[test]
+    fn parses_net_tools_output() {
+        let sockets = parse_netstat_output(NET_TOOLS);
+        assert_eq!(sockets.len(), 6);
+
+        let postgres = &sockets[0];
+        assert_eq!(postgres.protocol, "TCP");
+        assert_eq!(postgres.local_address, "127.0.0.1");
+        assert_eq!(postgres.local_port, 5432);
+        assert_eq!(postgres.state, "LISTENING");
+        assert_eq!(postgres.pids, vec![901]);
+
+        // 没有权限查看的进程
+        assert!(sockets[1].pids.is_empty());
+
+        // 程序名中含有空格
+        assert_eq!(sockets[2].local_address, "::");
+        assert_eq!(sockets[2].pids, vec![1234]);
+        assert_eq!(sockets[3].state, "ESTABLISHED");
+        assert_eq!(sockets[3].remote_address, "10.0.0.9");
+        assert_eq!(sockets[3].pids, vec![3120]);
+
+        let dhcp = sockets.iter().find(|s| s.local_port == 68).unwrap();
+        assert_eq!(dhcp.protocol, "UDP");
+        assert_eq!(dhcp.state, "LISTENING");
+        assert_eq!(dhcp.pids, vec![812]);
+
+        let dhcpv6 = sockets.iter().find(|s| s.local_port == 546).unwrap();
+        assert_eq!(dhcpv6.local_address, "::");
+        assert!(dhcpv6.pids.is_empty());
+    }
+
+    #[test]
+    fn ignores_headers_and_other_lines() {
+        let output = "Active UNIX domain sockets (servers and established)\nunix  2      [ ACC ]     STREAM     LISTENING     21521    1/systemd\n";
+        assert!(parse_netstat_output(output).is_empty());
+        assert!(parse_netstat_output("  TCP    0.0.0.0:135\n").is_empty());
+    }
+}
@@ -1,209 +1,688 @@
-﻿use std::process::Command;
-use serde::{Deserialize, Serialize};
-use sysinfo::{System, Pid, RefreshKind};
-use crate::commands::port_monitor::{ProcessInfo, ProjectInfo, ActionSuggestion};
+﻿use std::error::Error;
+use sysinfo::{System, Pid};
+use crate::commands::port_monitor::{ConnectionInfo, PortInfo, ProcessInfo, ProjectInfo};
+use crate::monitor::model::{ActionKind, ActionSuggestion, RiskLevel};
+use crate::monitor::netstat::NetstatBackend;
+use crate::monitor::proc_net::ProcNetBackend;
+use crate::monitor::process_sampler;
+use crate::monitor::ss::SsBackend;
 use crate::rules::project_detector;
 use crate::rules::process_rules::ProcessRules;
-use crate::utils::process_utils;
+use crate::rules::protection_policy::{ProtectionDecision, ProtectionPolicy, ProtectionSeverity};
 
+/// 后端采集到的原始套接字，状态名称统一为netstat风格（LISTENING、ESTABLISHED等）
+#[derive(Debug, Clone)]
+pub struct RawSocket {
+    pub protocol: String,
+    pub local_address: String,
+    pub local_port: u16,
+    pub remote_address: String,
+    pub remote_port: u16,
+    pub state: String,
+    /// 持有该套接字的全部进程，SO_REUSEPORT或预先fork的服务会有多个
+    pub pids: Vec<u32>,
+}
+
+/// 套接字采集后端，不同平台和环境可以使用不同的数据来源
+pub trait SocketBackend: Send {
+    fn name(&self) -> &'static str;
+
+    fn sockets(&self) -> Result<Vec<RawSocket>, Box<dyn Error>>;
+
+    /// 只采集监听中的套接字，能直接过滤的后端可以覆盖此方法
+    fn listening_sockets(&self) -> Result<Vec<RawSocket>, Box<dyn Error>> {
+        Ok(self
+            .sockets()?
+            .into_iter()
+            .filter(|socket| socket.state == "LISTENING")
+            .collect())
+    }
+}
+
+/// 端口扫描引擎，Tauri命令和端口监视线程都通过它获取端口信息
 pub struct PortMonitor {
     system: System,
     process_rules: ProcessRules,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PortInfo {
-    pub port: u16,
-    pub protocol: String,
-    pub status: String,
-    pub process: ProcessInfo,
-    pub project: Option<ProjectInfo>,
-    pub suggestions: Vec<ActionSuggestion>,
+    policy: ProtectionPolicy,
+    // 按优先级排列，前一个失败时依次尝试后一个
+    backends: Vec<Box<dyn SocketBackend>>,
+    active_backend: Option<&'static str>,
 }
 
 impl PortMonitor {
     pub fn new() -> Self {
-        let system = System::new_with_specifics(
-            RefreshKind::everything()
-        );
-        
+        Self::with_backends(default_backends())
+    }
+
+    pub fn with_backend(backend: Box<dyn SocketBackend>) -> Self {
+        Self::with_backends(vec![backend])
+    }
+
+    /// 进程表在首次采集时才读取，只采集套接字时不需要构建
+    pub fn with_backends(backends: Vec<Box<dyn SocketBackend>>) -> Self {
         PortMonitor {
-            system,
+            system: System::new(),
             process_rules: ProcessRules::new(),
+            policy: ProtectionPolicy::new(),
+            backends,
+            active_backend: None,
         }
     }
-    
+
+    /// 使用用户配置的保护策略生成操作建议，默认使用当前平台的默认规则
+    pub fn with_policy(mut self, policy: ProtectionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// 最近一次成功采集数据的后端名称，尚未采集时为None
+    pub fn backend_name(&self) -> Option<&'static str> {
+        self.active_backend
+    }
+
     pub fn refresh(&mut self) {
-        self.system.refresh_all();
+        // 端口监视线程长期复用同一个引擎，之后启动的进程也需要读取命令行
+        self.system.refresh_processes_specifics(process_sampler::sampling_refresh_kind());
     }
-    
-    pub fn get_listening_ports(&mut self) -> Result<Vec<PortInfo>, Box<dyn std::error::Error>> {
+
+    pub fn get_listening_ports(&mut self) -> Result<Vec<PortInfo>, Box<dyn Error>> {
+        self.refresh();
+
+        let sockets = self.listening_sockets()?;
+        Ok(self.resolve_listening_ports(&self.system, sockets))
+    }
+
+    /// 只采集监听套接字，不刷新进程表，之后通过 `resolve_listening_ports` 解析所属进程
+    pub fn listening_sockets(&mut self) -> Result<Vec<RawSocket>, Box<dyn Error>> {
+        self.collect_sockets(true).map(|(_, sockets)| sockets)
+    }
+
+    /// 使用调用方提供的进程快照（如采样器的快照）解析监听套接字的所属进程
+    pub fn resolve_listening_ports(&self, system: &System, sockets: Vec<RawSocket>) -> Vec<PortInfo> {
+        let backend = self.active_backend.unwrap_or_default();
+
+        sockets
+            .into_iter()
+            .filter_map(|socket| self.build_port_info(system, socket, backend))
+            .collect()
+    }
+
+    pub fn get_connections(&mut self) -> Result<Vec<ConnectionInfo>, Box<dyn Error>> {
         self.refresh();
-        
-        let mut ports = Vec::new();
-        
-        // 使用netstat获取端口信息
-        let output = if cfg!(target_os = "windows") {
-            Command::new("netstat")
-                .args(["-ano", "-p", "TCP"])
-                .output()?
-        } else {
-            Command::new("netstat")
-                .args(["-tlnp"])
-                .output()?
-        };
 
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        
-        for line in output_str.lines() {
-            if let Some(port_info) = self.parse_netstat_line(line) {
-                ports.push(port_info);
+        let sockets = self.sockets()?;
+        Ok(resolve_connections(&self.system, sockets))
+    }
+
+    /// 采集全部套接字，不刷新进程表，之后通过 `resolve_connections` 解析所属进程
+    pub fn sockets(&mut self) -> Result<Vec<RawSocket>, Box<dyn Error>> {
+        self.collect_sockets(false).map(|(_, sockets)| sockets)
+    }
+
+    /// 最近一次采集时刷新的进程快照
+    pub fn system(&self) -> &System {
+        &self.system
+    }
+
+    pub fn process_rules(&self) -> &ProcessRules {
+        &self.process_rules
+    }
+
+    /// 按进程规则和保护策略为端口的主进程生成操作建议
+    pub fn suggestions(
+        &self,
+        process: &ProcessInfo,
+        owners: &[ProcessInfo],
+        project: &Option<ProjectInfo>,
+        port: u16,
+    ) -> Vec<ActionSuggestion> {
+        generate_suggestions(process, owners, project, port, &self.process_rules, &self.policy, &self.system)
+    }
+
+    /// 补充未持有套接字的主进程并把主进程排在最前
+    ///
+    /// 用于合并多个套接字的持有者，例如SO_REUSEPORT的工作进程各自监听同一端口。
+    pub fn complete_owners(&self, owners: Vec<ProcessInfo>) -> Vec<ProcessInfo> {
+        complete_owners(&self.system, owners)
+    }
+
+    // 依次尝试各个后端，记录成功的后端；全部失败时汇总各后端的错误
+    fn collect_sockets(&mut self, listening_only: bool) -> Result<(&'static str, Vec<RawSocket>), Box<dyn Error>> {
+        let mut errors = Vec::new();
+
+        for backend in &self.backends {
+            let result = if listening_only {
+                backend.listening_sockets()
+            } else {
+                backend.sockets()
+            };
+
+            match result {
+                Ok(sockets) => {
+                    self.active_backend = Some(backend.name());
+                    return Ok((backend.name(), sockets));
+                }
+                Err(e) => errors.push(format!("{}: {}", backend.name(), e)),
             }
         }
 
-        Ok(ports)
+        self.active_backend = None;
+        Err(format!("所有套接字采集方式均失败: {}", errors.join("; ")).into())
     }
-    
-    fn parse_netstat_line(&self, line: &str) -> Option<PortInfo> {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        
-        if parts.len() < 4 {
+
+    fn build_port_info(&self, system: &System, socket: RawSocket, backend: &str) -> Option<PortInfo> {
+        // 端口为0的UDP套接字尚未绑定
+        if socket.state != "LISTENING" || socket.local_port == 0 {
             return None;
         }
 
-        // Windows netstat格式: TCP 0.0.0.0:3000 0.0.0.0:0 LISTENING 1234
-        if cfg!(target_os = "windows") && parts.len() >= 5 {
-            if parts[0] != "TCP" || parts[3] != "LISTENING" {
-                return None;
+        let owners = resolve_owners(system, &socket.pids);
+
+        // 非root用户看不到其他用户进程的fd，这类端口仍然列出，所属进程记为未知
+        let (process_info, project_info, suggestions) = match owners.first() {
+            Some(process_info) => {
+                // 使用智能识别引擎检测项目信息
+                let project_info = project_detector::detect_project(process_info, socket.local_port);
+
+                // 生成操作建议
+                let suggestions = generate_suggestions(
+                    process_info,
+                    &owners,
+                    &project_info,
+                    socket.local_port,
+                    &self.process_rules,
+                    &self.policy,
+                    system,
+                );
+                (process_info.clone(), project_info, suggestions)
             }
+            None => (ProcessInfo::unknown(), None, vec![unknown_owner_suggestion()]),
+        };
+
+        Some(PortInfo {
+            port: socket.local_port,
+            local_address: socket.local_address,
+            protocol: socket.protocol,
+            status: socket.state,
+            process: process_info,
+            owners,
+            project: project_info,
+            suggestions,
+            reservation: None,
+            reservation_conflict: false,
+            health: None,
+            fingerprint: None,
+            backend: backend.to_string(),
+        })
+    }
+}
+
+/// 使用调用方提供的进程快照（如采样器的快照）解析各连接的所属进程
+pub fn resolve_connections(system: &System, sockets: Vec<RawSocket>) -> Vec<ConnectionInfo> {
+    sockets
+        .into_iter()
+        .map(|socket| ConnectionInfo {
+            process: resolve_owners(system, &socket.pids).into_iter().next(),
+            protocol: socket.protocol,
+            local_address: socket.local_address,
+            local_port: socket.local_port,
+            remote_address: socket.remote_address,
+            remote_port: socket.remote_port,
+            state: socket.state,
+        })
+        .collect()
+}
+
+// 解析套接字的全部持有进程，主进程排在最前
+fn resolve_owners(system: &System, pids: &[u32]) -> Vec<ProcessInfo> {
+    let owners: Vec<ProcessInfo> = pids
+        .iter()
+        .filter_map(|&pid| process_info_by_pid(system, pid))
+        .collect();
+
+    complete_owners(system, owners)
+}
+
+fn complete_owners(system: &System, mut owners: Vec<ProcessInfo>) -> Vec<ProcessInfo> {
+    // SO_REUSEPORT的工作进程各自创建套接字，主进程本身可能并不监听，
+    // 此时把同名的共同父进程作为主进程补充进来
+    if owners.len() > 1 {
+        let parent_pid = owners[0].parent_pid;
+        let shared_parent = parent_pid.filter(|&ppid| {
+            ppid > 1 && owners.iter().all(|o| o.parent_pid == Some(ppid))
+        });
 
-            let local_addr = parts[1];
-            let port = self.extract_port_from_addr(local_addr)?;
-            let pid: u32 = parts[4].parse().ok()?;
-
-            let process_info = self.get_process_info_by_pid(pid)?;
-            
-            // 使用智能识别引擎检测项目信息
-            let project_info = project_detector::detect_project(&process_info, port);
-            
-            // 生成操作建议
-            let suggestions = self.generate_suggestions(&process_info, &project_info);
-
-            return Some(PortInfo {
-                port,
-                protocol: "TCP".to_string(),
-                status: "LISTENING".to_string(),
-                process: process_info,
-                project: project_info,
-                suggestions,
-            });
+        if let Some(parent) = shared_parent.and_then(|ppid| process_info_by_pid(system, ppid)) {
+            if parent.name == owners[0].name {
+                owners.push(parent);
+            }
         }
+    }
+
+    order_owners(&mut owners);
+    owners
+}
 
-        None
+fn process_info_by_pid(system: &System, pid: u32) -> Option<ProcessInfo> {
+    // PID 0 是系统空闲进程，Windows上TIME_WAIT连接都会归到它名下
+    if pid == 0 {
+        return None;
     }
-    
-    fn extract_port_from_addr(&self, addr: &str) -> Option<u16> {
-        addr.split(':').last()?.parse().ok()
+
+    let process = system.process(Pid::from(pid as usize))?;
+
+    Some(ProcessInfo {
+        pid,
+        name: process.name().to_string(),
+        exe_path: process.exe().map(|path| path.to_string_lossy().to_string()),
+        cmd: process.cmd().to_vec(),
+        parent_pid: process.parent().map(|ppid| ppid.as_u32()),
+    })
+}
+
+fn unknown_owner_suggestion() -> ActionSuggestion {
+    ActionSuggestion::new(
+        "以管理员身份查看",
+        "端口正在被监听，但无权限查看所属进程，请以管理员（root）身份重新运行".to_string(),
+        RiskLevel::None,
+        ActionKind::Manual,
+    )
+}
+
+/// 父进程不在持有者之中的进程视为主进程排在前面，同级按PID排序
+pub fn order_owners(owners: &mut [ProcessInfo]) {
+    let pids: Vec<u32> = owners.iter().map(|o| o.pid).collect();
+
+    owners.sort_by_key(|o| {
+        let is_child = o.parent_pid.map_or(false, |ppid| pids.contains(&ppid));
+        (is_child, o.pid)
+    });
+}
+
+/// Linux上优先直接读取/proc/net，不可用时依次退回ss和netstat；其他平台使用netstat
+pub fn default_backends() -> Vec<Box<dyn SocketBackend>> {
+    if cfg!(target_os = "linux") {
+        vec![
+            Box::new(ProcNetBackend::new("/proc")),
+            Box::new(SsBackend),
+            Box::new(NetstatBackend),
+        ]
+    } else {
+        vec![Box::new(NetstatBackend)]
     }
-    
-    fn get_process_info_by_pid(&self, pid: u32) -> Option<ProcessInfo> {
-        let process = self.system.process(Pid::from(pid as usize))?;
-        
-        Some(ProcessInfo {
-            pid,
-            name: process.name().to_string(),
-            exe_path: process.exe().map(|path| path.to_string_lossy().to_string()),
-            cmd: process.cmd().to_vec(),
-        })
+}
+
+// 支持 0.0.0.0:3000、[::1]:8080、:::80 以及 [fe80::1%4]:5353 等形式
+pub fn extract_port_from_addr(addr: &str) -> Option<u16> {
+    addr.rsplit_once(':')?.1.parse().ok()
+}
+
+pub fn extract_host_from_addr(addr: &str) -> Option<String> {
+    let host = addr.rsplit_once(':')?.0;
+    let host = host
+        .strip_prefix('[')
+        .and_then(|h| h.strip_suffix(']'))
+        .unwrap_or(host);
+
+    Some(host.to_string())
+}
+
+/// 将各工具输出的状态名称统一为netstat（Windows）风格
+pub fn normalize_state(state: &str) -> String {
+    match state.to_uppercase().replace('-', "_").as_str() {
+        "LISTEN" | "LISTENING" | "UNCONN" => "LISTENING",
+        "ESTAB" | "ESTABLISHED" => "ESTABLISHED",
+        "SYN_SENT" => "SYN_SENT",
+        "SYN_RECV" | "SYN_RECEIVED" => "SYN_RECV",
+        "FIN_WAIT1" | "FIN_WAIT_1" => "FIN_WAIT1",
+        "FIN_WAIT2" | "FIN_WAIT_2" => "FIN_WAIT2",
+        "TIME_WAIT" => "TIME_WAIT",
+        "CLOSE" | "CLOSED" => "CLOSE",
+        "CLOSE_WAIT" => "CLOSE_WAIT",
+        "LAST_ACK" => "LAST_ACK",
+        "CLOSING" => "CLOSING",
+        _ => "UNKNOWN",
     }
-    
-    fn generate_suggestions(
-        &self,
-        process: &ProcessInfo, 
-        project: &Option<ProjectInfo>
-    ) -> Vec<ActionSuggestion> {
-        let mut suggestions = Vec::new();
+    .to_string()
+}
 
-        // 首先检查是否有预定义的规则
-        if let Some(rule) = self.process_rules.get_rule_by_name(&process.name) {
-            for action in &rule.actions {
-                let (description, risk_level) = match action.as_str() {
-                    "停止服务" => ("终止进程释放端口", "低"),
-                    "重启" => ("重启服务", "低"),
-                    "查看日志" => ("查看应用日志", "无"),
-                    "关闭" => ("关闭应用", "低"),
-                    "查看容器" => ("显示相关Docker容器信息", "无"),
-                    "停止容器" => ("停止Docker容器释放端口", "中"),
-                    "查看任务管理器" => ("查看浏览器任务管理器", "无"),
-                    "保存文档" => ("保存当前文档后关闭", "低"),
-                    "查看服务详情" => ("查看Windows服务详情", "无"),
-                    _ => ("执行操作", "中"),
-                };
-                
-                suggestions.push(ActionSuggestion {
-                    action: action.clone(),
-                    description: description.to_string(),
-                    risk_level: risk_level.to_string(),
-                });
+/// `owners` 为共享同一套接字的全部进程，其中有 `process` 的子进程时优先建议停止主进程
+///
+/// 终止类建议会按保护策略调整：禁止操作的进程只建议查看详情，需要确认的进程不建议强制终止。
+pub fn generate_suggestions(
+    process: &ProcessInfo,
+    owners: &[ProcessInfo],
+    project: &Option<ProjectInfo>,
+    port: u16,
+    process_rules: &ProcessRules,
+    policy: &ProtectionPolicy,
+    system: &System,
+) -> Vec<ActionSuggestion> {
+    // 进程已退出时无法评估，按未受保护处理
+    let decision = policy.evaluate(system, process.pid, "kill").ok();
+    let suggestions = suggest_actions(process, owners, project, port, process_rules);
+
+    match decision {
+        Some(decision) => apply_protection(suggestions, &decision),
+        None => suggestions,
+    }
+}
+
+// 进程规则中的操作名称对应 `execute_action` 执行的操作
+fn rule_action(action: &str, pid: u32, port: u16) -> (&'static str, RiskLevel, ActionKind) {
+    match action {
+        "停止服务" => ("终止进程释放端口", RiskLevel::Low, ActionKind::StopProcess { pid }),
+        // 重启会终止整个进程树并按记录的命令行重新启动
+        "重启" => ("重启服务", RiskLevel::Medium, ActionKind::RestartProcess { pid }),
+        "查看日志" => ("查看进程正在写入的日志文件", RiskLevel::None, ActionKind::ShowProcessLogs { pid }),
+        "关闭" => ("关闭应用", RiskLevel::Low, ActionKind::StopProcess { pid }),
+        "查看容器" => ("显示相关Docker容器信息", RiskLevel::None, ActionKind::ShowContainerOnPort { port }),
+        "停止容器" => ("停止Docker容器释放端口", RiskLevel::Medium, ActionKind::StopContainerOnPort { port }),
+        "查看镜像" => ("查看占用端口的容器及其镜像", RiskLevel::None, ActionKind::ShowContainerOnPort { port }),
+        // Docker由系统服务管理器或Docker Desktop托管，直接重启进程会脱离其管理
+        "重启Docker" => ("通过Docker Desktop或系统服务管理器重启Docker", RiskLevel::Medium, ActionKind::Manual),
+        "查看任务管理器" => ("查看浏览器任务管理器", RiskLevel::None, ActionKind::OpenTaskManager),
+        "保存文档" => ("保存当前文档后关闭", RiskLevel::Low, ActionKind::Manual),
+        "查看服务详情" => ("查看Windows服务详情", RiskLevel::None, ActionKind::ShowProcessDetail { pid }),
+        _ => ("执行操作", RiskLevel::Medium, ActionKind::Manual),
+    }
+}
+
+fn suggest_actions(
+    process: &ProcessInfo,
+    owners: &[ProcessInfo],
+    project: &Option<ProjectInfo>,
+    port: u16,
+    process_rules: &ProcessRules,
+) -> Vec<ActionSuggestion> {
+    let mut suggestions = Vec::new();
+    let pid = process.pid;
+
+    // 单独结束某个工作进程只会被主进程重新拉起
+    let worker_count = owners
+        .iter()
+        .filter(|o| o.parent_pid == Some(process.pid))
+        .count();
+    if worker_count > 0 {
+        suggestions.push(ActionSuggestion::new(
+            "停止主进程",
+            format!(
+                "终止主进程 {} (PID {})，其 {} 个工作进程会随之退出",
+                process.name, process.pid, worker_count
+            ),
+            RiskLevel::Low,
+            ActionKind::StopProcess { pid },
+        ));
+    }
+
+    // 首先检查是否有预定义的规则
+    if let Some(rule) = process_rules.get_rule_by_name(&process.name) {
+        for action in &rule.actions {
+            let (description, risk_level, kind) = rule_action(action, pid, port);
+            // "停止主进程"与规则中的"停止服务"、"关闭"可能是同一个操作，只保留第一条
+            if suggestions.iter().any(|s| s.kind == kind) {
+                continue;
             }
-            
-            return suggestions;
+            suggestions.push(ActionSuggestion::new(action, description.to_string(), risk_level, kind));
         }
 
-        // 如果没有预定义规则，使用通用逻辑
-        match process.name.as_str() {
-            "node.exe" | "node" => {
-                suggestions.push(ActionSuggestion {
-                    action: "停止服务".to_string(),
-                    description: "终止Node.js进程释放端口".to_string(),
-                    risk_level: "低".to_string(),
-                });
-                
-                if project.is_some() {
-                    suggestions.push(ActionSuggestion {
-                        action: "重启服务".to_string(),
-                        description: "重启开发服务器".to_string(),
-                        risk_level: "低".to_string(),
-                    });
-                    
-                    suggestions.push(ActionSuggestion {
-                        action: "打开浏览器".to_string(),
-                        description: "在浏览器中查看应用".to_string(),
-                        risk_level: "无".to_string(),
-                    });
-                }
+        return suggestions;
+    }
+
+    // 如果没有预定义规则，使用通用逻辑
+    match process.name.as_str() {
+        "node.exe" | "node" => {
+            if worker_count == 0 {
+                suggestions.push(ActionSuggestion::new(
+                    "停止服务",
+                    "终止Node.js进程释放端口".to_string(),
+                    RiskLevel::Low,
+                    ActionKind::StopProcess { pid },
+                ));
             }
-            "docker-proxy.exe" | "docker-proxy" => {
-                suggestions.push(ActionSuggestion {
-                    action: "查看容器".to_string(),
-                    description: "显示相关Docker容器信息".to_string(),
-                    risk_level: "无".to_string(),
-                });
-                
-                suggestions.push(ActionSuggestion {
-                    action: "停止容器".to_string(),
-                    description: "停止Docker容器释放端口".to_string(),
-                    risk_level: "中".to_string(),
-                });
+
+            if project.is_some() {
+                suggestions.push(ActionSuggestion::new(
+                    "重启服务",
+                    "重启开发服务器".to_string(),
+                    RiskLevel::Low,
+                    ActionKind::RestartProcess { pid },
+                ));
+
+                suggestions.push(ActionSuggestion::new(
+                    "打开浏览器",
+                    "在浏览器中查看应用".to_string(),
+                    RiskLevel::None,
+                    ActionKind::OpenBrowser { url: format!("http://localhost:{}", port) },
+                ));
             }
-            _ => {
-                // 检查是否是系统进程
-                if process_utils::is_system_process(&process.name) {
-                    suggestions.push(ActionSuggestion {
-                        action: "查看详情".to_string(),
-                        description: "查看系统进程详细信息".to_string(),
-                        risk_level: "无".to_string(),
-                    });
-                } else {
-                    suggestions.push(ActionSuggestion {
-                        action: "终止进程".to_string(),
-                        description: "强制终止进程释放端口".to_string(),
-                        risk_level: "高".to_string(),
-                    });
+        }
+        "docker-proxy.exe" | "docker-proxy" => {
+            suggestions.push(ActionSuggestion::new(
+                "查看容器",
+                "显示相关Docker容器信息".to_string(),
+                RiskLevel::None,
+                ActionKind::ShowContainerOnPort { port },
+            ));
+
+            suggestions.push(ActionSuggestion::new(
+                "停止容器",
+                "停止Docker容器释放端口".to_string(),
+                RiskLevel::Medium,
+                ActionKind::StopContainerOnPort { port },
+            ));
+        }
+        _ => {
+            suggestions.push(ActionSuggestion::new(
+                "终止进程",
+                "强制终止进程释放端口".to_string(),
+                RiskLevel::High,
+                ActionKind::ForceKillProcess { pid },
+            ));
+        }
+    }
+
+    suggestions
+}
+
+fn is_termination(kind: &ActionKind) -> bool {
+    matches!(
+        kind,
+        ActionKind::StopProcess { .. }
+            | ActionKind::ForceKillProcess { .. }
+            | ActionKind::KillProcessTree { .. }
+            | ActionKind::RestartProcess { .. }
+    )
+}
+
+/// 按保护策略的决定调整终止类建议，文件占用分析给出的解决方案同样经过这里
+pub fn apply_protection(suggestions: Vec<ActionSuggestion>, decision: &ProtectionDecision) -> Vec<ActionSuggestion> {
+    let severity = match decision.severity {
+        Some(severity) => severity,
+        None => return suggestions,
+    };
+    let reasons = decision
+        .matched_rules
+        .iter()
+        .map(|rule| rule.description.as_str())
+        .collect::<Vec<_>>()
+        .join("；");
+    let pid = decision.pid;
+
+    let mut adjusted: Vec<ActionSuggestion> = Vec::new();
+    for mut suggestion in suggestions {
+        if !is_termination(&suggestion.kind) {
+            adjusted.push(suggestion);
+            continue;
+        }
+
+        match severity {
+            ProtectionSeverity::Block => continue,
+            ProtectionSeverity::Confirm => {
+                // 受保护的进程只建议优雅退出，操作时需要确认
+                if suggestion.kind == (ActionKind::ForceKillProcess { pid }) {
+                    suggestion.action = "停止进程".to_string();
+                    suggestion.kind = ActionKind::StopProcess { pid };
                 }
+                suggestion.risk_level = RiskLevel::High;
+                suggestion.description = format!("{}（{}，需要确认）", suggestion.description, reasons);
+            }
+            ProtectionSeverity::Warn => {
+                suggestion.description = format!("{}（{}）", suggestion.description, reasons);
+            }
+        }
+
+        if !adjusted.iter().any(|s| s.kind == suggestion.kind) {
+            adjusted.push(suggestion);
+        }
+    }
+
+    if severity == ProtectionSeverity::Block && !adjusted.iter().any(|s| s.kind == (ActionKind::ShowProcessDetail { pid })) {
+        adjusted.push(ActionSuggestion::new(
+            "查看详情",
+            format!("受保护的进程，禁止终止：{}", reasons),
+            RiskLevel::None,
+            ActionKind::ShowProcessDetail { pid },
+        ));
+    }
+
+    adjusted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::protection_policy::{ProtectionMatcher, ProtectionRule};
+
+    fn current_process() -> (System, ProcessInfo) {
+        let pid = std::process::id();
+        let mut system = System::new();
+        system.refresh_process(Pid::from(pid as usize));
+
+        let process = ProcessInfo {
+            pid,
+            name: "port-test".to_string(),
+            exe_path: None,
+            cmd: Vec::new(),
+            parent_pid: None,
+        };
+        (system, process)
+    }
+
+    fn pid_policy(pid: u32, severity: ProtectionSeverity) -> ProtectionPolicy {
+        ProtectionPolicy::from_rules(vec![ProtectionRule {
+            id: "test".to_string(),
+            description: "测试保护".to_string(),
+            matcher: ProtectionMatcher::Pid(vec![pid]),
+            severity,
+        }])
+    }
+
+    fn suggest(policy: &ProtectionPolicy) -> Vec<ActionSuggestion> {
+        let (system, process) = current_process();
+        generate_suggestions(&process, &[], &None, 8080, &ProcessRules::new(), policy, &system)
+    }
+
+    #[test]
+    fn unprotected_process_can_be_force_killed() {
+        let policy = ProtectionPolicy::from_rules(Vec::new());
+        let pid = std::process::id();
+
+        let suggestions = suggest(&policy);
+        assert!(suggestions.iter().any(|s| s.kind == ActionKind::ForceKillProcess { pid }));
+    }
+
+    #[test]
+    fn blocked_process_only_offers_details() {
+        let pid = std::process::id();
+        let suggestions = suggest(&pid_policy(pid, ProtectionSeverity::Block));
+
+        assert!(!suggestions.iter().any(|s| is_termination(&s.kind)));
+        let detail = suggestions
+            .iter()
+            .find(|s| s.kind == ActionKind::ShowProcessDetail { pid })
+            .unwrap();
+        assert!(detail.description.contains("测试保护"));
+    }
+
+    #[test]
+    fn confirmed_process_is_stopped_gracefully() {
+        let pid = std::process::id();
+        let suggestions = suggest(&pid_policy(pid, ProtectionSeverity::Confirm));
+
+        assert!(!suggestions.iter().any(|s| s.kind == ActionKind::ForceKillProcess { pid }));
+        let stop = suggestions
+            .iter()
+            .find(|s| s.kind == ActionKind::StopProcess { pid })
+            .unwrap();
+        assert_eq!(stop.risk_level, RiskLevel::High);
+        assert!(stop.description.contains("需要确认"));
+    }
+
+    #[test]
+    fn warned_process_keeps_suggestions() {
+        let pid = std::process::id();
+        let suggestions = suggest(&pid_policy(pid, ProtectionSeverity::Warn));
+
+        let kill = suggestions
+            .iter()
+            .find(|s| s.kind == ActionKind::ForceKillProcess { pid })
+            .unwrap();
+        assert!(kill.description.contains("测试保护"));
+    }
+
+    #[test]
+    fn rule_actions_map_to_executable_kinds() {
+        let (_, _, logs) = rule_action("查看日志", 42, 8080);
+        assert_eq!(logs, ActionKind::ShowProcessLogs { pid: 42 });
+
+        let (_, _, container) = rule_action("停止容器", 42, 8080);
+        assert_eq!(container, ActionKind::StopContainerOnPort { port: 8080 });
+
+        let (_, risk, restart) = rule_action("重启", 42, 8080);
+        assert_eq!(restart, ActionKind::RestartProcess { pid: 42 });
+        assert!(risk > RiskLevel::Low);
+
+        // Docker服务不能按命令行重启
+        let (_, _, docker) = rule_action("重启Docker", 42, 8080);
+        assert_eq!(docker, ActionKind::Manual);
+
+        // 规则库中的每个操作名称都要有对应的操作，"保存文档"和"重启Docker"需要手动完成
+        for rule in &ProcessRules::new().rules {
+            for action in &rule.actions {
+                let (description, _, kind) = rule_action(action, 42, 8080);
+                assert!(description != "执行操作", "{} 的操作 {} 没有对应的处理", rule.process_name, action);
+                assert!(kind != ActionKind::Manual || action == "保存文档" || action == "重启Docker");
             }
         }
+    }
 
-        suggestions
+    #[test]
+    fn master_stop_is_not_duplicated() {
+        let master = ProcessInfo {
+            pid: 100,
+            name: "node.exe".to_string(),
+            exe_path: None,
+            cmd: Vec::new(),
+            parent_pid: None,
+        };
+        let worker = ProcessInfo {
+            pid: 101,
+            parent_pid: Some(100),
+            ..master.clone()
+        };
+
+        for name in ["node.exe", "node"] {
+            let master = ProcessInfo { name: name.to_string(), ..master.clone() };
+            let owners = vec![master.clone(), worker.clone()];
+            let suggestions = suggest_actions(&master, &owners, &None, 3000, &ProcessRules::new());
+
+            let stops = suggestions
+                .iter()
+                .filter(|s| s.kind == ActionKind::StopProcess { pid: 100 })
+                .count();
+            assert_eq!(stops, 1, "{}", name);
+            assert_eq!(suggestions[0].action, "停止主进程");
+        }
     }
 }
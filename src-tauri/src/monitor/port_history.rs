@@ -0,0 +1,354 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use crate::monitor::port_watcher::PortEvent;
+
+// 保留时长的上限，约一年
+pub const MAX_RETENTION_HOURS: u64 = 24 * 365;
+
+// 追加记录时按时长裁剪的最小间隔，避免端口变化不频繁时过期记录一直留在文件中
+const AGE_COMPACT_INTERVAL_SECS: i64 = 3600;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PortHistoryRecord {
+    pub timestamp: i64,
+    pub event: String,
+    pub port: u16,
+    pub protocol: String,
+    pub local_address: String,
+    pub pid: u32,
+    pub process_name: String,
+    pub cmd: Vec<String>,
+    pub project: Option<String>,
+    pub project_type: Option<String>,
+    pub previous_pid: Option<u32>,
+    pub previous_process_name: Option<String>,
+}
+
+impl PortHistoryRecord {
+    pub fn from_event(event: &PortEvent, timestamp: i64) -> Self {
+        let (name, port, previous) = match event {
+            PortEvent::Opened(port) => ("opened", port, None),
+            PortEvent::Closed(port) => ("closed", port, None),
+            PortEvent::OwnerChanged(change) => ("owner-changed", &change.port, Some(&change.previous_process)),
+        };
+
+        PortHistoryRecord {
+            timestamp,
+            event: name.to_string(),
+            port: port.port,
+            protocol: port.protocol.clone(),
+            local_address: port.local_address.clone(),
+            pid: port.process.pid,
+            process_name: port.process.name.clone(),
+            cmd: port.process.cmd.clone(),
+            project: port.project.as_ref().map(|p| p.name.clone()),
+            project_type: port.project.as_ref().map(|p| p.project_type.clone()),
+            previous_pid: previous.map(|p| p.pid),
+            previous_process_name: previous.map(|p| p.name.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PortHistoryRetention {
+    pub max_records: usize,
+    pub max_age_hours: u64,
+}
+
+impl Default for PortHistoryRetention {
+    fn default() -> Self {
+        PortHistoryRetention {
+            max_records: 10000,
+            max_age_hours: 24 * 7,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct PortHistoryQuery {
+    pub port: Option<u16>,
+    pub process_name: Option<String>,
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub limit: Option<usize>,
+}
+
+struct HistoryData {
+    records: Vec<PortHistoryRecord>,
+    retention: PortHistoryRetention,
+    last_compacted: i64,
+}
+
+/// 端口历史存储，记录以JSON Lines格式追加写入，超出保留限制时整体重写
+pub struct PortHistoryStore {
+    records_path: PathBuf,
+    retention_path: PathBuf,
+    data: Mutex<HistoryData>,
+}
+
+impl PortHistoryStore {
+    pub fn load(data_dir: PathBuf) -> Self {
+        let records_path = data_dir.join("port_history.jsonl");
+        let retention_path = data_dir.join("port_history_retention.json");
+
+        let retention = fs::read_to_string(&retention_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        // 跳过无法解析的行，例如写入中途崩溃留下的半行
+        let records = fs::read_to_string(&records_path)
+            .map(|content| {
+                content
+                    .lines()
+                    .filter_map(|line| serde_json::from_str(line).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let store = PortHistoryStore {
+            records_path,
+            retention_path,
+            data: Mutex::new(HistoryData {
+                records,
+                retention,
+                last_compacted: 0,
+            }),
+        };
+
+        let _ = store.compact(&mut store.data.lock().unwrap());
+        store
+    }
+
+    pub fn append(&self, new_records: Vec<PortHistoryRecord>) -> Result<(), String> {
+        if new_records.is_empty() {
+            return Ok(());
+        }
+
+        let mut data = self.data.lock().unwrap();
+
+        ensure_parent_dir(&self.records_path)?;
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.records_path)
+            .map_err(|e| format!("打开端口历史文件失败: {}", e))?;
+
+        for record in &new_records {
+            let line = serde_json::to_string(record).map_err(|e| format!("序列化端口历史失败: {}", e))?;
+            writeln!(file, "{}", line).map_err(|e| format!("写入端口历史失败: {}", e))?;
+        }
+
+        data.records.extend(new_records);
+
+        // 留出一定余量，避免每次追加都重写文件；超过一小时未裁剪时也按保留时长裁剪一次
+        let over_limit = data.records.len() > data.retention.max_records + data.retention.max_records / 10;
+        let compact_due = chrono::Local::now().timestamp() - data.last_compacted >= AGE_COMPACT_INTERVAL_SECS;
+        if over_limit || compact_due {
+            self.compact(&mut data)?;
+        }
+
+        Ok(())
+    }
+
+    /// 按条件查询历史记录，结果按时间倒序排列，不返回超出保留时长的记录
+    pub fn query(&self, query: &PortHistoryQuery) -> Vec<PortHistoryRecord> {
+        let data = self.data.lock().unwrap();
+        let process_name = query.process_name.as_ref().map(|n| n.to_lowercase());
+        let cutoff = retention_cutoff(&data.retention);
+
+        data.records
+            .iter()
+            .rev()
+            .filter(|r| r.timestamp >= cutoff)
+            .filter(|r| query.port.map_or(true, |port| r.port == port))
+            .filter(|r| {
+                process_name
+                    .as_ref()
+                    .map_or(true, |name| r.process_name.to_lowercase().contains(name.as_str()))
+            })
+            .filter(|r| query.since.map_or(true, |since| r.timestamp >= since))
+            .filter(|r| query.until.map_or(true, |until| r.timestamp <= until))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
+    }
+
+    pub fn retention(&self) -> PortHistoryRetention {
+        self.data.lock().unwrap().retention.clone()
+    }
+
+    pub fn set_retention(&self, retention: PortHistoryRetention) -> Result<(), String> {
+        let mut data = self.data.lock().unwrap();
+
+        let content = serde_json::to_string_pretty(&retention)
+            .map_err(|e| format!("序列化保留策略失败: {}", e))?;
+        ensure_parent_dir(&self.retention_path)?;
+        fs::write(&self.retention_path, content).map_err(|e| format!("保存保留策略失败: {}", e))?;
+
+        data.retention = retention;
+        self.compact(&mut data)
+    }
+
+    pub fn clear(&self) -> Result<(), String> {
+        let mut data = self.data.lock().unwrap();
+        data.records.clear();
+        self.compact(&mut data)
+    }
+
+    // 按保留策略裁剪记录并重写文件
+    fn compact(&self, data: &mut HistoryData) -> Result<(), String> {
+        let cutoff = retention_cutoff(&data.retention);
+        data.records.retain(|r| r.timestamp >= cutoff);
+        data.last_compacted = chrono::Local::now().timestamp();
+
+        if data.records.len() > data.retention.max_records {
+            let excess = data.records.len() - data.retention.max_records;
+            data.records.drain(..excess);
+        }
+
+        ensure_parent_dir(&self.records_path)?;
+
+        let mut content = String::new();
+        for record in &data.records {
+            let line = serde_json::to_string(record).map_err(|e| format!("序列化端口历史失败: {}", e))?;
+            content.push_str(&line);
+            content.push('\n');
+        }
+
+        fs::write(&self.records_path, content).map_err(|e| format!("写入端口历史失败: {}", e))
+    }
+}
+
+// 早于该时间戳的记录已超出保留时长
+fn retention_cutoff(retention: &PortHistoryRetention) -> i64 {
+    // 保留策略文件可能被手动修改，避免超大的小时数溢出
+    let max_age_secs = i64::try_from(retention.max_age_hours.saturating_mul(3600)).unwrap_or(i64::MAX);
+    chrono::Local::now().timestamp().saturating_sub(max_age_secs)
+}
+
+fn ensure_parent_dir(path: &Path) -> Result<(), String> {
+    match path.parent() {
+        Some(parent) => fs::create_dir_all(parent).map_err(|e| format!("创建数据目录失败: {}", e)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store(name: &str) -> (PathBuf, PortHistoryStore) {
+        let dir = std::env::temp_dir().join(format!("port_history_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let store = PortHistoryStore::load(dir.clone());
+        (dir, store)
+    }
+
+    fn record(timestamp: i64, port: u16, process_name: &str) -> PortHistoryRecord {
+        PortHistoryRecord {
+            timestamp,
+            event: "opened".to_string(),
+            port,
+            protocol: "TCP".to_string(),
+            local_address: "0.0.0.0".to_string(),
+            pid: 100,
+            process_name: process_name.to_string(),
+            cmd: Vec::new(),
+            project: None,
+            project_type: None,
+            previous_pid: None,
+            previous_process_name: None,
+        }
+    }
+
+    fn ports(records: &[PortHistoryRecord]) -> Vec<u16> {
+        records.iter().map(|r| r.port).collect()
+    }
+
+    #[test]
+    fn queries_newest_first_with_filters() {
+        let (dir, store) = temp_store("query");
+        let now = chrono::Local::now().timestamp();
+        store
+            .append(vec![
+                record(now - 30, 3000, "node"),
+                record(now - 20, 8080, "nginx"),
+                record(now - 10, 3001, "Node"),
+            ])
+            .unwrap();
+
+        assert_eq!(ports(&store.query(&PortHistoryQuery::default())), vec![3001, 8080, 3000]);
+
+        let by_name = PortHistoryQuery { process_name: Some("NODE".to_string()), ..PortHistoryQuery::default() };
+        assert_eq!(ports(&store.query(&by_name)), vec![3001, 3000]);
+
+        let by_port = PortHistoryQuery { port: Some(8080), ..PortHistoryQuery::default() };
+        assert_eq!(ports(&store.query(&by_port)), vec![8080]);
+
+        let window = PortHistoryQuery {
+            since: Some(now - 25),
+            until: Some(now - 5),
+            limit: Some(1),
+            ..PortHistoryQuery::default()
+        };
+        assert_eq!(ports(&store.query(&window)), vec![3001]);
+
+        // 重新加载后记录仍在
+        let reloaded = PortHistoryStore::load(dir.clone());
+        assert_eq!(ports(&reloaded.query(&PortHistoryQuery::default())), vec![3001, 8080, 3000]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn retention_limits_records_and_age() {
+        let (dir, store) = temp_store("retention");
+        let now = chrono::Local::now().timestamp();
+        store
+            .append(vec![record(now - 3, 1, "a"), record(now - 2, 2, "b"), record(now - 1, 3, "c")])
+            .unwrap();
+
+        store
+            .set_retention(PortHistoryRetention { max_records: 2, max_age_hours: 1 })
+            .unwrap();
+        assert_eq!(ports(&store.query(&PortHistoryQuery::default())), vec![3, 2]);
+
+        // 保留策略和裁剪结果都已写入文件
+        let reloaded = PortHistoryStore::load(dir.clone());
+        assert_eq!(reloaded.retention().max_records, 2);
+        assert_eq!(ports(&reloaded.query(&PortHistoryQuery::default())), vec![3, 2]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn expired_records_are_hidden_and_compacted_on_record() {
+        let (dir, store) = temp_store("expiry");
+        store
+            .set_retention(PortHistoryRetention { max_records: 100, max_age_hours: 1 })
+            .unwrap();
+        let now = chrono::Local::now().timestamp();
+
+        store.append(vec![record(now - 7200, 1, "old")]).unwrap();
+        assert!(store.query(&PortHistoryQuery::default()).is_empty());
+
+        // 距上次裁剪不足一小时，过期记录暂时留在文件中
+        let content = fs::read_to_string(dir.join("port_history.jsonl")).unwrap();
+        assert!(content.contains("\"old\""));
+
+        store.data.lock().unwrap().last_compacted = now - AGE_COMPACT_INTERVAL_SECS;
+        store.append(vec![record(now, 2, "new")]).unwrap();
+
+        let content = fs::read_to_string(dir.join("port_history.jsonl")).unwrap();
+        assert!(!content.contains("\"old\""));
+        assert_eq!(ports(&store.query(&PortHistoryQuery::default())), vec![2]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::commands::port_monitor::{PortInfo, ProcessInfo};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PortOwnerChange {
+    pub port: PortInfo,
+    pub previous_process: ProcessInfo,
+}
+
+#[derive(Debug, Clone)]
+pub enum PortEvent {
+    Opened(PortInfo),
+    Closed(PortInfo),
+    OwnerChanged(PortOwnerChange),
+}
+
+impl PortEvent {
+    /// 推送给前端的事件名称
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            PortEvent::Opened(_) => "port-opened",
+            PortEvent::Closed(_) => "port-closed",
+            PortEvent::OwnerChanged(_) => "port-owner-changed",
+        }
+    }
+}
+
+// (协议, 本地地址, 端口)
+type PortKey = (String, String, u16);
+
+pub fn index_ports(ports: Vec<PortInfo>) -> HashMap<PortKey, PortInfo> {
+    ports
+        .into_iter()
+        .map(|p| ((p.protocol.clone(), p.local_address.clone(), p.port), p))
+        .collect()
+}
+
+/// 比较前后两次端口快照，得出新开、关闭以及所属进程变化的端口
+pub fn diff_port_snapshots(
+    previous: &HashMap<PortKey, PortInfo>,
+    current: &HashMap<PortKey, PortInfo>,
+) -> Vec<PortEvent> {
+    let mut events = Vec::new();
+
+    for (key, port) in current {
+        match previous.get(key) {
+            None => events.push(PortEvent::Opened(port.clone())),
+            Some(old) if old.process.pid != port.process.pid => {
+                events.push(PortEvent::OwnerChanged(PortOwnerChange {
+                    port: port.clone(),
+                    previous_process: old.process.clone(),
+                }));
+            }
+            Some(_) => {}
+        }
+    }
+
+    for (key, port) in previous {
+        if !current.contains_key(key) {
+            events.push(PortEvent::Closed(port.clone()));
+        }
+    }
+
+    events
+}
+
+/// 后台端口监视线程，按固定间隔采样并回调差异事件
+///
+/// `scan` 在每次采样时调用，由调用方决定使用的进程快照和保护策略，
+/// 用户修改保护策略后无需重启监视线程。
+pub struct PortWatcher {
+    stop_tx: Sender<()>,
+    interval: Duration,
+}
+
+impl PortWatcher {
+    pub fn spawn<S, F>(interval: Duration, mut scan: S, mut on_events: F) -> Self
+    where
+        S: FnMut() -> Result<Vec<PortInfo>, String> + Send + 'static,
+        F: FnMut(Vec<PortEvent>) + Send + 'static,
+    {
+        let (stop_tx, stop_rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            let mut previous = None;
+
+            loop {
+                if let Ok(ports) = scan() {
+                    let current = index_ports(ports);
+
+                    // 第一次采样只作为基准，不推送事件
+                    if let Some(previous) = &previous {
+                        let events = diff_port_snapshots(previous, &current);
+                        if !events.is_empty() {
+                            on_events(events);
+                        }
+                    }
+
+                    previous = Some(current);
+                }
+
+                // 收到停止信号或发送端被丢弃时退出
+                match stop_rx.recv_timeout(interval) {
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    _ => break,
+                }
+            }
+        });
+
+        PortWatcher { stop_tx, interval }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    pub fn stop(self) {
+        let _ = self.stop_tx.send(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listening(port: u16, pid: u32) -> PortInfo {
+        PortInfo {
+            port,
+            local_address: "0.0.0.0".to_string(),
+            protocol: "TCP".to_string(),
+            status: "LISTENING".to_string(),
+            process: ProcessInfo {
+                pid,
+                name: "node".to_string(),
+                exe_path: None,
+                cmd: Vec::new(),
+                parent_pid: None,
+            },
+            owners: Vec::new(),
+            project: None,
+            suggestions: Vec::new(),
+            reservation: None,
+            reservation_conflict: false,
+            health: None,
+            fingerprint: None,
+            backend: "procfs".to_string(),
+        }
+    }
+
+    #[test]
+    fn reports_opened_ports() {
+        let previous = index_ports(vec![listening(3000, 100)]);
+        let current = index_ports(vec![listening(3000, 100), listening(8080, 200)]);
+
+        let events = diff_port_snapshots(&previous, &current);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(&events[0], PortEvent::Opened(p) if p.port == 8080 && p.process.pid == 200));
+    }
+
+    #[test]
+    fn reports_closed_ports() {
+        let previous = index_ports(vec![listening(3000, 100), listening(8080, 200)]);
+        let current = index_ports(vec![listening(3000, 100)]);
+
+        let events = diff_port_snapshots(&previous, &current);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(&events[0], PortEvent::Closed(p) if p.port == 8080));
+    }
+
+    #[test]
+    fn reports_owner_changes() {
+        let previous = index_ports(vec![listening(3000, 100)]);
+        let current = index_ports(vec![listening(3000, 300)]);
+
+        let events = diff_port_snapshots(&previous, &current);
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            PortEvent::OwnerChanged(change) => {
+                assert_eq!(change.port.process.pid, 300);
+                assert_eq!(change.previous_process.pid, 100);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unchanged_snapshot_has_no_events() {
+        let previous = index_ports(vec![listening(3000, 100), listening(8080, 200)]);
+        let current = index_ports(vec![listening(8080, 200), listening(3000, 100)]);
+
+        assert!(diff_port_snapshots(&previous, &current).is_empty());
+    }
+}
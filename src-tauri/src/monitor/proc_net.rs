@@ -0,0 +1,415 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::{Path, PathBuf};
+use crate::monitor::port::{RawSocket, SocketBackend};
+
+/// 直接读取 /proc/net 的套接字表，并通过 /proc/<pid>/fd 关联所属进程
+pub struct ProcNetBackend {
+    proc_root: PathBuf,
+}
+
+impl ProcNetBackend {
+    /// `proc_root` 通常为 "/proc"，也可以指向保存下来的proc文件副本
+    pub fn new<P: Into<PathBuf>>(proc_root: P) -> Self {
+        ProcNetBackend {
+            proc_root: proc_root.into(),
+        }
+    }
+}
+
+impl SocketBackend for ProcNetBackend {
+    fn name(&self) -> &'static str {
+        "procfs"
+    }
+
+    fn sockets(&self) -> Result<Vec<RawSocket>, Box<dyn Error>> {
+        let inode_map = map_socket_inodes(&self.proc_root)?;
+
+        // TIME_WAIT等已脱离进程的套接字inode为0，没有所属进程
+        let sockets = read_socket_table(&self.proc_root)?
+            .into_iter()
+            .map(|socket| RawSocket {
+                pids: inode_map.get(&socket.inode).cloned().unwrap_or_default(),
+                protocol: socket.protocol,
+                local_address: socket.local_addr.to_string(),
+                local_port: socket.local_port,
+                remote_address: socket.remote_addr.to_string(),
+                remote_port: socket.remote_port,
+                state: socket.state,
+            })
+            .collect();
+
+        Ok(sockets)
+    }
+}
+
+/// /proc/net/{tcp,tcp6,udp,udp6} 中的一条套接字记录
+#[derive(Debug, Clone)]
+pub struct SocketEntry {
+    pub protocol: String,
+    pub local_addr: IpAddr,
+    pub local_port: u16,
+    pub remote_addr: IpAddr,
+    pub remote_port: u16,
+    pub state: String,
+    pub uid: u32,
+    pub inode: u64,
+}
+
+impl SocketEntry {
+    pub fn is_listening(&self) -> bool {
+        self.state == "LISTENING"
+    }
+}
+
+// (文件名, 协议, 是否IPv6)
+const SOCKET_TABLES: [(&str, &str, bool); 4] = [
+    ("tcp", "TCP", false),
+    ("tcp6", "TCP", true),
+    ("udp", "UDP", false),
+    ("udp6", "UDP", true),
+];
+
+/// 读取 `proc_root/net` 下的全部套接字表，缺失的文件（例如禁用了IPv6）会被跳过
+pub fn read_socket_table(proc_root: &Path) -> io::Result<Vec<SocketEntry>> {
+    let mut entries = Vec::new();
+
+    for (file_name, protocol, ipv6) in SOCKET_TABLES {
+        let content = match fs::read_to_string(proc_root.join("net").join(file_name)) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+
+        entries.extend(parse_socket_table(&content, protocol, ipv6));
+    }
+
+    Ok(entries)
+}
+
+/// 解析单个套接字表文件的内容
+///
+/// 格式: `sl local_address rem_address st tx_queue:rx_queue tr:tm->when retrnsmt uid timeout inode ...`
+pub fn parse_socket_table(content: &str, protocol: &str, ipv6: bool) -> Vec<SocketEntry> {
+    content
+        .lines()
+        .skip(1) // 表头
+        .filter_map(|line| parse_socket_line(line, protocol, ipv6))
+        .collect()
+}
+
+fn parse_socket_line(line: &str, protocol: &str, ipv6: bool) -> Option<SocketEntry> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+
+    if parts.len() < 10 {
+        return None;
+    }
+
+    let (local_addr, local_port) = parse_hex_endpoint(parts[1], ipv6)?;
+    let (remote_addr, remote_port) = parse_hex_endpoint(parts[2], ipv6)?;
+    let state_code = u8::from_str_radix(parts[3], 16).ok()?;
+
+    Some(SocketEntry {
+        protocol: protocol.to_string(),
+        local_addr,
+        local_port,
+        remote_addr,
+        remote_port,
+        state: socket_state_name(protocol, state_code).to_string(),
+        uid: parts[7].parse().ok()?,
+        inode: parts[9].parse().ok()?,
+    })
+}
+
+// 地址部分按内核的主机字节序输出，每4字节为一组，端口为普通的十六进制数
+fn parse_hex_endpoint(endpoint: &str, ipv6: bool) -> Option<(IpAddr, u16)> {
+    let (addr_hex, port_hex) = endpoint.split_once(':')?;
+    let port = u16::from_str_radix(port_hex, 16).ok()?;
+
+    let addr = if ipv6 {
+        if addr_hex.len() != 32 {
+            return None;
+        }
+
+        let mut octets = [0u8; 16];
+        for (i, chunk) in octets.chunks_mut(4).enumerate() {
+            let word = u32::from_str_radix(&addr_hex[i * 8..i * 8 + 8], 16).ok()?;
+            chunk.copy_from_slice(&word.to_ne_bytes());
+        }
+
+        IpAddr::V6(Ipv6Addr::from(octets))
+    } else {
+        if addr_hex.len() != 8 {
+            return None;
+        }
+
+        let word = u32::from_str_radix(addr_hex, 16).ok()?;
+        IpAddr::V4(Ipv4Addr::from(word.to_ne_bytes()))
+    };
+
+    Some((addr, port))
+}
+
+// 状态名称与netstat保持一致，未连接的UDP套接字（内核中为TCP_CLOSE）视为监听
+fn socket_state_name(protocol: &str, code: u8) -> &'static str {
+    if protocol == "UDP" {
+        return match code {
+            0x01 => "ESTABLISHED",
+            _ => "LISTENING",
+        };
+    }
+
+    match code {
+        0x01 => "ESTABLISHED",
+        0x02 => "SYN_SENT",
+        0x03 => "SYN_RECV",
+        0x04 => "FIN_WAIT1",
+        0x05 => "FIN_WAIT2",
+        0x06 => "TIME_WAIT",
+        0x07 => "CLOSE",
+        0x08 => "CLOSE_WAIT",
+        0x09 => "LAST_ACK",
+        0x0A => "LISTENING",
+        0x0B => "CLOSING",
+        _ => "UNKNOWN",
+    }
+}
+
+/// 遍历 `proc_root/<pid>/fd`，建立套接字inode到持有进程PID的映射
+///
+/// fork出的子进程会继承同一个套接字，因此一个inode可能对应多个PID。
+/// 没有权限读取的进程会被静默跳过，因此非root用户只能看到自己的进程。
+pub fn map_socket_inodes(proc_root: &Path) -> io::Result<HashMap<u64, Vec<u32>>> {
+    let mut inode_map = HashMap::new();
+
+    for entry in fs::read_dir(proc_root)? {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(_) => continue,
+        };
+
+        let pid: u32 = match entry.file_name().to_str().and_then(|name| name.parse().ok()) {
+            Some(pid) => pid,
+            None => continue,
+        };
+
+        let fds = match fs::read_dir(entry.path().join("fd")) {
+            Ok(fds) => fds,
+            Err(_) => continue,
+        };
+
+        for fd in fds.flatten() {
+            if let Ok(target) = fs::read_link(fd.path()) {
+                if let Some(inode) = parse_socket_link(&target.to_string_lossy()) {
+                    let pids = inode_map.entry(inode).or_insert_with(Vec::new);
+                    if !pids.contains(&pid) {
+                        pids.push(pid);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(inode_map)
+}
+
+// fd符号链接的目标形如 "socket:[12345]"
+fn parse_socket_link(target: &str) -> Option<u64> {
+    target
+        .strip_prefix("socket:[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TCP: &str = "\
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41001 1 0000000000000000 100 0 0 10 0
+   1: 00000000:1538 00000000:0000 0A 00000000:00000000 00:00000000 00000000   114        0 41002 1 0000000000000000 100 0 0 10 0
+   2: 0100007F:D431 0100007F:1F90 01 00000000:00000000 00:00000000 00000000  1000        0 41003 1 0000000000000000 20 4 30 10 -1
+   3: 0100007F:D432 0100007F:1F90 06 00000000:00000000 03:000011B5 00000000     0        0 0 3 0000000000000000
+";
+
+    const TCP6: &str = "\
+  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000000000000:1538 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000   114        0 41004 1 0000000000000000 100 0 0 10 0
+   1: 00000000000000000000000001000000:46A0 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 41005 1 0000000000000000 100 0 0 10 0
+   2: 0000000000000000FFFF00000100007F:0BB8 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41006 1 0000000000000000 100 0 0 10 0
+";
+
+    const UDP: &str = "\
+   sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops
+  100: 3500007F:0035 00000000:0000 07 00000000:00000000 00:00000000 00000000   101        0 41007 2 0000000000000000 0
+  101: 0100007F:A1B2 0100007F:0035 01 00000000:00000000 00:00000000 00000000  1000        0 41008 2 0000000000000000 0
+";
+
+    const UDP6: &str = "\
+   sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops
+  200: 00000000000000000000000000000000:14E9 00000000000000000000000000000000:0000 07 00000000:00000000 00:00000000 00000000   104        0 41009 2 0000000000000000 0
+";
+
+    // 在临时目录中构造 net/* 套接字表和 <pid>/fd 符号链接
+    fn fixture_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("proc_net_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("net")).unwrap();
+        root
+    }
+
+    #[cfg(unix)]
+    fn add_fd(root: &Path, pid: &str, fd: u32, target: &str) {
+        let dir = root.join(pid).join("fd");
+        fs::create_dir_all(&dir).unwrap();
+        std::os::unix::fs::symlink(target, dir.join(fd.to_string())).unwrap();
+    }
+
+    #[test]
+    fn parses_ipv4_tcp_table() {
+        let entries = parse_socket_table(TCP, "TCP", false);
+        assert_eq!(entries.len(), 4);
+
+        let listener = &entries[0];
+        assert_eq!(listener.local_addr, IpAddr::V4(Ipv4Addr::LOCALHOST));
+        assert_eq!(listener.local_port, 8080);
+        assert_eq!(listener.remote_port, 0);
+        assert!(listener.is_listening());
+        assert_eq!(listener.uid, 1000);
+        assert_eq!(listener.inode, 41001);
+
+        assert_eq!(entries[1].local_addr, IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+        assert_eq!(entries[1].local_port, 5432);
+
+        assert_eq!(entries[2].state, "ESTABLISHED");
+        assert_eq!(entries[2].remote_port, 8080);
+
+        // TIME_WAIT已脱离进程，inode为0
+        assert_eq!(entries[3].state, "TIME_WAIT");
+        assert_eq!(entries[3].inode, 0);
+    }
+
+    #[test]
+    fn parses_ipv6_tcp_table() {
+        let entries = parse_socket_table(TCP6, "TCP", true);
+        assert_eq!(entries.len(), 3);
+
+        // 与 0.0.0.0:5432 同时存在，构成双栈监听
+        assert_eq!(entries[0].local_addr, IpAddr::V6(Ipv6Addr::UNSPECIFIED));
+        assert_eq!(entries[0].local_port, 5432);
+        assert_eq!(entries[0].local_addr.to_string(), "::");
+
+        assert_eq!(entries[1].local_addr, IpAddr::V6(Ipv6Addr::LOCALHOST));
+        assert_eq!(entries[1].local_port, 18080);
+
+        let mapped = Ipv4Addr::LOCALHOST.to_ipv6_mapped();
+        assert_eq!(entries[2].local_addr, IpAddr::V6(mapped));
+        assert_eq!(entries[2].local_addr.to_string(), "::ffff:127.0.0.1");
+        assert_eq!(entries[2].local_port, 3000);
+    }
+
+    #[test]
+    fn unconnected_udp_is_listening() {
+        let entries = parse_socket_table(UDP, "UDP", false);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].local_addr, IpAddr::V4(Ipv4Addr::new(127, 0, 0, 53)));
+        assert_eq!(entries[0].local_port, 53);
+        assert!(entries[0].is_listening());
+        assert_eq!(entries[1].state, "ESTABLISHED");
+
+        let entries = parse_socket_table(UDP6, "UDP", true);
+        assert_eq!(entries[0].local_port, 5353);
+        assert!(entries[0].is_listening());
+    }
+
+    #[test]
+    fn skips_malformed_lines() {
+        let content = "header\n   0: 0100007F:1F90 00000000:0000 0A\n   1: 7F:1F90 00000000:0000 0A 0 0 0 0 0 1\n";
+        assert!(parse_socket_table(content, "TCP", false).is_empty());
+        // 长度不符的IPv6地址
+        let content = "header\n   0: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000 0 0 1\n";
+        assert!(parse_socket_table(content, "TCP", true).is_empty());
+    }
+
+    #[test]
+    fn parses_socket_links() {
+        assert_eq!(parse_socket_link("socket:[41001]"), Some(41001));
+        assert_eq!(parse_socket_link("pipe:[41001]"), None);
+        assert_eq!(parse_socket_link("anon_inode:[eventfd]"), None);
+        assert_eq!(parse_socket_link("/dev/null"), None);
+        assert_eq!(parse_socket_link("socket:[abc]"), None);
+    }
+
+    #[test]
+    fn reads_tables_from_proc_root() {
+        let root = fixture_root("tables");
+        fs::write(root.join("net/tcp"), TCP).unwrap();
+        fs::write(root.join("net/udp"), UDP).unwrap();
+        // 禁用IPv6时没有 tcp6/udp6
+
+        let entries = read_socket_table(&root).unwrap();
+        assert_eq!(entries.len(), 6);
+        assert_eq!(entries.iter().filter(|e| e.protocol == "UDP").count(), 2);
+
+        fs::write(root.join("net/tcp6"), TCP6).unwrap();
+        fs::write(root.join("net/udp6"), UDP6).unwrap();
+        assert_eq!(read_socket_table(&root).unwrap().len(), 10);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn maps_inodes_to_pids() {
+        let root = fixture_root("fds");
+        add_fd(&root, "100", 3, "socket:[41001]");
+        add_fd(&root, "100", 4, "/dev/null");
+        add_fd(&root, "100", 5, "pipe:[900]");
+        // fork出的子进程继承同一个监听套接字
+        add_fd(&root, "101", 3, "socket:[41001]");
+        add_fd(&root, "101", 7, "socket:[41003]");
+        add_fd(&root, "self", 3, "socket:[41009]");
+        fs::create_dir_all(root.join("102")).unwrap();
+
+        let map = map_socket_inodes(&root).unwrap();
+        let mut owners = map[&41001].clone();
+        owners.sort_unstable();
+        assert_eq!(owners, vec![100, 101]);
+        assert_eq!(map[&41003], vec![101]);
+        assert!(!map.contains_key(&41009));
+        assert_eq!(map.len(), 2);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn backend_joins_tables_and_owners() {
+        let root = fixture_root("backend");
+        fs::write(root.join("net/tcp"), TCP).unwrap();
+        fs::write(root.join("net/tcp6"), TCP6).unwrap();
+        add_fd(&root, "200", 3, "socket:[41002]");
+        add_fd(&root, "200", 4, "socket:[41004]");
+
+        let backend = ProcNetBackend::new(&root);
+        let listening = backend.listening_sockets().unwrap();
+        assert_eq!(listening.len(), 5);
+
+        let postgres: Vec<&RawSocket> = listening.iter().filter(|s| s.local_port == 5432).collect();
+        assert_eq!(postgres.len(), 2);
+        assert!(postgres.iter().all(|s| s.pids == vec![200]));
+        assert!(postgres.iter().any(|s| s.local_address == "0.0.0.0"));
+        assert!(postgres.iter().any(|s| s.local_address == "::"));
+
+        // 其他用户的进程不可见时没有持有者
+        let other = listening.iter().find(|s| s.local_port == 8080).unwrap();
+        assert!(other.pids.is_empty());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
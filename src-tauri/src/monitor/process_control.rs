@@ -0,0 +1,167 @@
+use std::fs;
+use std::process::Command;
+use sysinfo::{Pid, Process, ProcessStatus, Signal};
+
+/// 暂停进程（SIGSTOP），Windows上不支持
+pub fn suspend(process: &Process) -> Result<(), String> {
+    send_signal(process, Signal::Stop, "暂停")
+}
+
+/// 恢复被暂停的进程（SIGCONT）
+pub fn resume(process: &Process) -> Result<(), String> {
+    send_signal(process, Signal::Continue, "恢复")
+}
+
+fn send_signal(process: &Process, signal: Signal, action: &str) -> Result<(), String> {
+    match process.kill_with(signal) {
+        Some(true) => Ok(()),
+        Some(false) => Err(format!("无法{}进程{}，可能权限不足", action, process.pid())),
+        None => Err(format!("当前系统不支持{}进程", action)),
+    }
+}
+
+/// 设置CPU调度优先级，`nice` 取值 -20（最高）到 19（最低）
+///
+/// 提高优先级（减小nice值）通常需要管理员权限。Windows上按区间映射为进程优先级类别，
+/// 最高只到High，RealTime会抢占系统关键线程，不通过这里设置。
+pub fn set_priority(pid: u32, nice: i32) -> Result<(), String> {
+    let class = windows_priority_class(nice)?;
+
+    if cfg!(target_os = "windows") {
+        let script = format!("(Get-Process -Id {}).PriorityClass = '{}'", pid, class);
+        run_command("powershell", &["-NoProfile", "-Command", &script])
+    } else {
+        run_command("renice", &["-n", &nice.to_string(), "-p", &pid.to_string()])
+    }
+}
+
+// 校验nice值并映射为Windows优先级类别
+fn windows_priority_class(nice: i32) -> Result<&'static str, String> {
+    let class = match nice {
+        -20..=-11 => "High",
+        -10..=-1 => "AboveNormal",
+        0 => "Normal",
+        1..=10 => "BelowNormal",
+        11..=19 => "Idle",
+        _ => return Err("优先级必须在-20到19之间".to_string()),
+    };
+    Ok(class)
+}
+
+/// 设置I/O调度优先级（仅Linux，依赖util-linux的ionice）
+///
+/// `class` 为 realtime、best-effort 或 idle，`level` 为0（最高）到7（最低），idle类别忽略level。
+pub fn set_io_priority(pid: u32, class: &str, level: Option<u8>) -> Result<(), String> {
+    if !cfg!(target_os = "linux") {
+        return Err("I/O优先级仅支持Linux系统".to_string());
+    }
+
+    let args = ionice_args(pid, class, level)?;
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    run_command("ionice", &args)
+}
+
+fn ionice_args(pid: u32, class: &str, level: Option<u8>) -> Result<Vec<String>, String> {
+    let class_id = match class {
+        "realtime" => "1",
+        "best-effort" => "2",
+        "idle" => "3",
+        _ => return Err(format!("不支持的I/O调度类别: {}", class)),
+    };
+
+    // idle类别没有优先级等级
+    let level = match level {
+        Some(level) if class_id != "3" => {
+            if level > 7 {
+                return Err("I/O优先级必须在0到7之间".to_string());
+            }
+            Some(level.to_string())
+        }
+        _ => None,
+    };
+
+    let mut args = vec!["-c".to_string(), class_id.to_string()];
+
+    if let Some(level) = level {
+        args.extend(["-n".to_string(), level]);
+    }
+
+    args.extend(["-p".to_string(), pid.to_string()]);
+    Ok(args)
+}
+
+fn run_command(program: &str, args: &[&str]) -> Result<(), String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("无法执行{}: {}", program, e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!("{}执行失败: {}", program, String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+/// 读取进程当前的nice值，仅Linux支持
+pub fn read_nice(pid: Pid) -> Option<i32> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    parse_stat_nice(&stat)
+}
+
+// /proc/<pid>/stat 中进程名可能包含空格，从最后一个 ')' 之后开始按字段切分，
+// nice为第19个字段
+fn parse_stat_nice(stat: &str) -> Option<i32> {
+    let (_, rest) = stat.rsplit_once(')')?;
+    rest.split_whitespace().nth(16)?.parse().ok()
+}
+
+pub fn is_stopped(process: &Process) -> bool {
+    matches!(process.status(), ProcessStatus::Stop | ProcessStatus::Tracing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nice_after_process_name() {
+        let stat = "1234 (web (worker) 1) S 1 1234 1234 0 -1 4194560 100 0 0 0 5 3 0 0 20 -5 4 0 100 0 0";
+        assert_eq!(parse_stat_nice(stat), Some(-5));
+
+        let plain = "42 (sleep) S 1 42 42 0 -1 4194304 90 0 0 0 0 0 0 0 20 10 1 0 500 0 0";
+        assert_eq!(parse_stat_nice(plain), Some(10));
+
+        assert_eq!(parse_stat_nice("42 (sleep) S 1"), None);
+        assert_eq!(parse_stat_nice(""), None);
+    }
+
+    #[test]
+    fn validates_priority_range() {
+        assert_eq!(windows_priority_class(-20), Ok("High"));
+        assert_eq!(windows_priority_class(-1), Ok("AboveNormal"));
+        assert_eq!(windows_priority_class(0), Ok("Normal"));
+        assert_eq!(windows_priority_class(10), Ok("BelowNormal"));
+        assert_eq!(windows_priority_class(19), Ok("Idle"));
+
+        assert!(windows_priority_class(-21).is_err());
+        assert!(windows_priority_class(20).is_err());
+        assert!(set_priority(std::process::id(), 20).is_err());
+    }
+
+    #[test]
+    fn validates_io_priority_levels() {
+        assert_eq!(ionice_args(7, "best-effort", Some(0)).unwrap(), vec!["-c", "2", "-n", "0", "-p", "7"]);
+        assert_eq!(ionice_args(7, "realtime", Some(7)).unwrap(), vec!["-c", "1", "-n", "7", "-p", "7"]);
+        assert_eq!(ionice_args(7, "best-effort", None).unwrap(), vec!["-c", "2", "-p", "7"]);
+        // idle类别忽略level，即使超出范围
+        assert_eq!(ionice_args(7, "idle", Some(9)).unwrap(), vec!["-c", "3", "-p", "7"]);
+
+        assert!(ionice_args(7, "best-effort", Some(8)).is_err());
+        assert!(ionice_args(7, "urgent", Some(0)).is_err());
+    }
+}
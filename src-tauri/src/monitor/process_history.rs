@@ -0,0 +1,334 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use sysinfo::{Process, System, ThreadKind};
+use crate::monitor::process_detail;
+
+// 每条序列最多保留的采样点数，默认2秒间隔下约30分钟
+pub const MAX_SAMPLES: usize = 900;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default)]
+pub struct ResourceSample {
+    pub timestamp: i64,
+    pub cpu_usage: f32,
+    /// 常驻内存（RSS）
+    pub memory: u64,
+    pub virtual_memory: u64,
+    /// 线程数，仅Linux可用
+    pub thread_count: Option<u32>,
+    /// 距上次采样的磁盘读写字节数
+    pub disk_read_bytes: u64,
+    pub disk_written_bytes: u64,
+    /// 汇总的进程数，按PID记录时恒为1
+    pub process_count: u32,
+}
+
+impl ResourceSample {
+    fn from_process(process: &Process, timestamp: i64) -> Self {
+        let disk_usage = process.disk_usage();
+
+        ResourceSample {
+            timestamp,
+            cpu_usage: process.cpu_usage(),
+            memory: process.memory(),
+            virtual_memory: process.virtual_memory(),
+            thread_count: None,
+            disk_read_bytes: disk_usage.read_bytes,
+            disk_written_bytes: disk_usage.written_bytes,
+            process_count: 1,
+        }
+    }
+
+    // 同名进程的采样相加，得到按进程名汇总的用量
+    fn accumulate(&mut self, other: &ResourceSample) {
+        self.cpu_usage += other.cpu_usage;
+        self.memory += other.memory;
+        self.virtual_memory += other.virtual_memory;
+        self.thread_count = match (self.thread_count, other.thread_count) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+        self.disk_read_bytes += other.disk_read_bytes;
+        self.disk_written_bytes += other.disk_written_bytes;
+        self.process_count += other.process_count;
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct ProcessHistoryQuery {
+    /// 按PID查询，与 `name` 二选一
+    pub pid: Option<u32>,
+    /// 按进程名查询，同名进程汇总，进程重启后序列不中断
+    pub name: Option<String>,
+    /// 时间分辨率（秒），同一时间桶内的采样取平均值，磁盘读写量求和；为0时返回原始采样
+    pub resolution_secs: u64,
+    pub since: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProcessHistorySeries {
+    pub pid: Option<u32>,
+    pub name: String,
+    pub resolution_secs: u64,
+    pub samples: Vec<ResourceSample>,
+}
+
+/// 单个进程的一次采样
+///
+/// 在持有进程快照锁时只复制内存中的数据，需要读取 /proc 的线程数在释放锁之后通过
+/// `read_thread_count` 补充，避免逐个读取文件时阻塞其他命令。
+pub struct ProcessSample {
+    pid: u32,
+    name: String,
+    start_time: u64,
+    sample: ResourceSample,
+}
+
+impl ProcessSample {
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    pub fn read_thread_count(&mut self) {
+        self.sample.thread_count = process_detail::read_thread_count(self.pid).map(|count| count as u32);
+    }
+}
+
+/// 从刚刷新过的进程快照中复制各进程的采样，线程数留空
+pub fn collect_samples(system: &System, timestamp: i64) -> Vec<ProcessSample> {
+    // Linux上用户态线程也会作为进程列出，只记录真正的进程和内核线程
+    system
+        .processes()
+        .iter()
+        .filter(|(_, process)| process.thread_kind() != Some(ThreadKind::Userland))
+        .map(|(pid, process)| ProcessSample {
+            pid: pid.as_u32(),
+            name: process.name().to_string(),
+            start_time: process.start_time(),
+            sample: ResourceSample::from_process(process, timestamp),
+        })
+        .collect()
+}
+
+struct PidSeries {
+    name: String,
+    // PID被复用时通过启动时间区分，避免把两个进程的数据拼到一起
+    start_time: u64,
+    samples: VecDeque<ResourceSample>,
+}
+
+/// 进程资源历史，按PID和进程名分别保存定长环形缓冲区
+///
+/// 进程退出后其PID序列立即丢弃；进程名序列保留到最后一个采样点超出保留窗口为止。
+pub struct ProcessHistory {
+    pids: HashMap<u32, PidSeries>,
+    names: HashMap<String, VecDeque<ResourceSample>>,
+    capacity: usize,
+    retention_secs: i64,
+}
+
+impl ProcessHistory {
+    pub fn new(capacity: usize, interval: Duration) -> Self {
+        ProcessHistory {
+            pids: HashMap::new(),
+            names: HashMap::new(),
+            capacity,
+            retention_secs: (interval.as_secs_f64() * capacity as f64).ceil() as i64,
+        }
+    }
+
+    /// 记录一次采样，`samples` 由 `collect_samples` 在同一时刻收集
+    pub fn record(&mut self, samples: Vec<ProcessSample>, timestamp: i64) {
+        let mut seen = HashSet::new();
+        let mut name_totals: HashMap<String, ResourceSample> = HashMap::new();
+
+        for ProcessSample { pid, name, start_time, sample } in samples {
+            seen.insert(pid);
+
+            let series = self.pids.entry(pid).or_insert_with(|| PidSeries {
+                name: name.clone(),
+                start_time,
+                samples: VecDeque::new(),
+            });
+            if series.start_time != start_time {
+                series.name = name.clone();
+                series.start_time = start_time;
+                series.samples.clear();
+            }
+            push_bounded(&mut series.samples, sample, self.capacity);
+
+            name_totals
+                .entry(name)
+                .or_insert(ResourceSample {
+                    timestamp,
+                    process_count: 0,
+                    ..ResourceSample::default()
+                })
+                .accumulate(&sample);
+        }
+
+        self.pids.retain(|pid, _| seen.contains(pid));
+
+        for (name, total) in name_totals {
+            let samples = self.names.entry(name).or_default();
+            push_bounded(samples, total, self.capacity);
+        }
+
+        let cutoff = timestamp - self.retention_secs;
+        self.names
+            .retain(|_, samples| samples.back().map_or(false, |last| last.timestamp >= cutoff));
+    }
+
+    pub fn query(&self, query: &ProcessHistoryQuery) -> Result<ProcessHistorySeries, String> {
+        let (pid, name, samples) = match (query.pid, &query.name) {
+            (Some(pid), None) => {
+                let series = self.pids.get(&pid).ok_or(format!("没有进程{}的历史数据", pid))?;
+                (Some(pid), series.name.clone(), &series.samples)
+            }
+            (None, Some(name)) => {
+                let samples = self.names.get(name).ok_or(format!("没有进程{}的历史数据", name))?;
+                (None, name.clone(), samples)
+            }
+            _ => return Err("必须且只能指定pid或进程名之一".to_string()),
+        };
+
+        let samples = samples
+            .iter()
+            .filter(|s| query.since.map_or(true, |since| s.timestamp >= since))
+            .copied();
+
+        Ok(ProcessHistorySeries {
+            pid,
+            name,
+            resolution_secs: query.resolution_secs,
+            samples: downsample(samples, query.resolution_secs),
+        })
+    }
+}
+
+fn push_bounded(samples: &mut VecDeque<ResourceSample>, sample: ResourceSample, capacity: usize) {
+    if samples.len() >= capacity {
+        samples.pop_front();
+    }
+    samples.push_back(sample);
+}
+
+// 按时间桶合并采样点，桶的时间戳取桶的起始时间
+//
+// 线程数可能在部分采样点缺失，只对读取到线程数的采样点求平均。
+fn downsample(samples: impl Iterator<Item = ResourceSample>, resolution_secs: u64) -> Vec<ResourceSample> {
+    if resolution_secs == 0 {
+        return samples.collect();
+    }
+
+    let resolution = resolution_secs as i64;
+    // (合计, 采样点数, 读取到线程数的采样点数)
+    let mut buckets: Vec<(ResourceSample, u32, u32)> = Vec::new();
+
+    for sample in samples {
+        let bucket_start = sample.timestamp - sample.timestamp.rem_euclid(resolution);
+        let has_threads = sample.thread_count.is_some() as u32;
+
+        match buckets.last_mut() {
+            Some((bucket, count, thread_samples)) if bucket.timestamp == bucket_start => {
+                bucket.accumulate(&sample);
+                *count += 1;
+                *thread_samples += has_threads;
+            }
+            _ => buckets.push((
+                ResourceSample {
+                    timestamp: bucket_start,
+                    ..sample
+                },
+                1,
+                has_threads,
+            )),
+        }
+    }
+
+    buckets
+        .into_iter()
+        .map(|(sum, count, thread_samples)| ResourceSample {
+            timestamp: sum.timestamp,
+            cpu_usage: sum.cpu_usage / count as f32,
+            memory: sum.memory / count as u64,
+            virtual_memory: sum.virtual_memory / count as u64,
+            thread_count: sum.thread_count.map(|threads| threads / thread_samples),
+            disk_read_bytes: sum.disk_read_bytes,
+            disk_written_bytes: sum.disk_written_bytes,
+            process_count: sum.process_count / count,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sysinfo::Pid;
+
+    fn current_samples(timestamp: i64) -> Vec<ProcessSample> {
+        let mut system = System::new();
+        system.refresh_process(Pid::from(std::process::id() as usize));
+        collect_samples(&system, timestamp)
+    }
+
+    #[test]
+    fn thread_count_is_read_after_collecting() {
+        let mut samples = current_samples(100);
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].sample.thread_count, None);
+
+        samples[0].read_thread_count();
+        if cfg!(target_os = "linux") {
+            assert!(samples[0].sample.thread_count.unwrap() >= 1);
+        }
+    }
+
+    #[test]
+    fn records_series_by_pid_and_name() {
+        let pid = std::process::id();
+        let mut history = ProcessHistory::new(2, Duration::from_secs(2));
+
+        for timestamp in [100, 102, 104] {
+            let mut samples = current_samples(timestamp);
+            samples.iter_mut().for_each(|sample| sample.read_thread_count());
+            history.record(samples, timestamp);
+        }
+
+        let series = history
+            .query(&ProcessHistoryQuery { pid: Some(pid), ..ProcessHistoryQuery::default() })
+            .unwrap();
+        let timestamps: Vec<i64> = series.samples.iter().map(|s| s.timestamp).collect();
+        assert_eq!(timestamps, vec![102, 104]);
+
+        let by_name = history
+            .query(&ProcessHistoryQuery { name: Some(series.name), ..ProcessHistoryQuery::default() })
+            .unwrap();
+        assert_eq!(by_name.samples.len(), 2);
+        assert_eq!(by_name.samples[1].process_count, 1);
+
+        // 进程退出后PID序列被丢弃
+        history.record(Vec::new(), 106);
+        assert!(history.query(&ProcessHistoryQuery { pid: Some(pid), ..ProcessHistoryQuery::default() }).is_err());
+    }
+
+    #[test]
+    fn downsampled_thread_count_ignores_missing_samples() {
+        let sample = |timestamp: i64, thread_count: Option<u32>| ResourceSample {
+            timestamp,
+            memory: 100,
+            thread_count,
+            process_count: 1,
+            ..ResourceSample::default()
+        };
+        let samples = vec![sample(10, Some(4)), sample(11, None), sample(12, Some(8)), sample(20, None)];
+
+        let buckets = downsample(samples.into_iter(), 10);
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].timestamp, 10);
+        assert_eq!(buckets[0].thread_count, Some(6));
+        assert_eq!(buckets[0].memory, 100);
+        assert_eq!(buckets[1].thread_count, None);
+    }
+}
@@ -0,0 +1,276 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sysinfo::{Process, System, ThreadKind, Uid};
+use crate::commands::port_monitor::PortInfo;
+use crate::commands::process_analyzer::{self, DetailedProcessInfo};
+use crate::monitor::process_detail::AccountCache;
+
+/// 进程查询条件，所有过滤条件之间为“且”的关系
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ProcessQuery {
+    /// 进程名子串，不区分大小写
+    pub name: Option<String>,
+    /// 进程名正则表达式
+    pub name_regex: Option<String>,
+    /// 用户名或用户ID（Windows上为SID）
+    pub user: Option<String>,
+    pub exe_prefix: Option<String>,
+    pub min_cpu: Option<f32>,
+    /// 最小内存占用（字节）
+    pub min_memory: Option<u64>,
+    /// 进程状态，如 "Run"、"Sleep"，不区分大小写
+    pub status: Option<String>,
+    pub has_listening_port: Option<bool>,
+    /// 监听端口上识别出的项目类型，如 "React"、"Spring"
+    pub project_type: Option<String>,
+    /// cpu | memory | pid | name | start_time
+    pub sort_by: String,
+    /// asc | desc
+    pub sort_order: String,
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+impl Default for ProcessQuery {
+    fn default() -> Self {
+        ProcessQuery {
+            name: None,
+            name_regex: None,
+            user: None,
+            exe_prefix: None,
+            min_cpu: None,
+            min_memory: None,
+            status: None,
+            has_listening_port: None,
+            project_type: None,
+            sort_by: "cpu".to_string(),
+            sort_order: "desc".to_string(),
+            offset: 0,
+            limit: Some(100),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProcessPage {
+    /// 满足条件的进程总数（分页前）
+    pub total: usize,
+    pub offset: usize,
+    pub processes: Vec<DetailedProcessInfo>,
+}
+
+impl ProcessQuery {
+    /// 是否需要扫描监听端口才能完成过滤
+    pub fn needs_ports(&self) -> bool {
+        self.has_listening_port.is_some() || self.project_type.is_some()
+    }
+}
+
+/// 按条件过滤、排序并分页，`ports` 仅在 `needs_ports()` 为真时需要提供
+pub fn query_processes(
+    system: &System,
+    query: &ProcessQuery,
+    ports: &[PortInfo],
+    nice_values: &HashMap<u32, i32>,
+    accounts: &AccountCache,
+) -> Result<ProcessPage, String> {
+    let name_regex = match &query.name_regex {
+        Some(pattern) => Some(Regex::new(pattern).map_err(|e| format!("无效的正则表达式: {}", e))?),
+        None => None,
+    };
+    let name = query.name.as_ref().map(|n| n.to_lowercase());
+    let compare = sort_comparator(&query.sort_by, &query.sort_order)?;
+
+    let listening_pids: HashSet<u32> = ports
+        .iter()
+        .flat_map(|p| p.owners.iter().map(|o| o.pid).chain(Some(p.process.pid)))
+        .collect();
+    let project_types = index_project_types(ports);
+
+    let mut processes: Vec<DetailedProcessInfo> = system
+        .processes()
+        .iter()
+        // Linux上sysinfo会把用户态线程也列为进程，内核线程保留
+        .filter(|(_, process)| process.thread_kind() != Some(ThreadKind::Userland))
+        .filter(|(pid, process)| {
+            let pid = pid.as_u32();
+
+            name.as_ref().map_or(true, |n| process.name().to_lowercase().contains(n.as_str()))
+                && name_regex.as_ref().map_or(true, |re| re.is_match(process.name()))
+                && query.user.as_ref().map_or(true, |user| user_matches(process, user, accounts))
+                && query.exe_prefix.as_ref().map_or(true, |prefix| {
+                    process.exe().map_or(false, |exe| exe.to_string_lossy().starts_with(prefix.as_str()))
+                })
+                && query.min_cpu.map_or(true, |min| process.cpu_usage() >= min)
+                && query.min_memory.map_or(true, |min| process.memory() >= min)
+                && query.status.as_ref().map_or(true, |status| {
+                    format!("{:?}", process.status()).eq_ignore_ascii_case(status)
+                })
+                && query.has_listening_port.map_or(true, |has| listening_pids.contains(&pid) == has)
+                && query.project_type.as_ref().map_or(true, |project_type| {
+                    project_types
+                        .get(&pid)
+                        .map_or(false, |types| types.iter().any(|t| t.eq_ignore_ascii_case(project_type)))
+                })
+        })
+        .map(|(pid, process)| {
+            process_analyzer::to_detailed_info(*pid, process, nice_values.get(&pid.as_u32()).copied())
+        })
+        .collect();
+
+    processes.sort_by(compare);
+
+    let total = processes.len();
+    let limit = query.limit.unwrap_or(total);
+    let processes = processes.into_iter().skip(query.offset).take(limit).collect();
+
+    Ok(ProcessPage {
+        total,
+        offset: query.offset,
+        processes,
+    })
+}
+
+type Comparator = fn(&DetailedProcessInfo, &DetailedProcessInfo) -> Ordering;
+type BoxedComparator = Box<dyn Fn(&DetailedProcessInfo, &DetailedProcessInfo) -> Ordering>;
+
+fn sort_comparator(sort_by: &str, sort_order: &str) -> Result<BoxedComparator, String> {
+    let compare: Comparator = match sort_by {
+        "cpu" => |a, b| a.cpu_usage.partial_cmp(&b.cpu_usage).unwrap_or(Ordering::Equal),
+        "memory" => |a, b| a.memory_usage.cmp(&b.memory_usage),
+        "pid" => |a, b| a.pid.cmp(&b.pid),
+        "name" => |a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+        "start_time" => |a, b| a.start_time.cmp(&b.start_time),
+        _ => return Err(format!("不支持的排序字段: {}", sort_by)),
+    };
+
+    // 相同时按PID排序，保证分页结果稳定
+    match sort_order {
+        "asc" => Ok(Box::new(move |a, b| compare(a, b).then(a.pid.cmp(&b.pid)))),
+        "desc" => Ok(Box::new(move |a, b| compare(b, a).then(a.pid.cmp(&b.pid)))),
+        _ => Err(format!("不支持的排序方向: {}", sort_order)),
+    }
+}
+
+fn user_matches(process: &Process, user: &str, accounts: &AccountCache) -> bool {
+    let uid = match process.user_id() {
+        Some(uid) => uid,
+        None => return false,
+    };
+
+    if user.parse::<Uid>().map_or(false, |query_uid| &query_uid == uid) {
+        return true;
+    }
+
+    accounts.user_name(uid).map_or(false, |name| name == user)
+}
+
+// 项目类型只能从监听端口上识别，进程的全部持有者都归入该项目
+fn index_project_types(ports: &[PortInfo]) -> HashMap<u32, Vec<String>> {
+    let mut types: HashMap<u32, Vec<String>> = HashMap::new();
+
+    for port in ports {
+        if let Some(project) = &port.project {
+            for pid in port.owners.iter().map(|o| o.pid).chain(Some(port.process.pid)) {
+                let entry = types.entry(pid).or_default();
+                if !entry.contains(&project.project_type) {
+                    entry.push(project.project_type.clone());
+                }
+            }
+        }
+    }
+
+    types
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::{Child, Command};
+    use sysinfo::Pid;
+    use crate::monitor::process_sampler::sampling_refresh_kind;
+
+    // 本进程及若干子进程组成的进程表
+    fn process_table(children: usize) -> (System, Vec<Child>) {
+        let children: Vec<Child> = (0..children)
+            .map(|_| Command::new("sleep").arg("30").spawn().unwrap())
+            .collect();
+
+        let mut system = System::new();
+        for pid in children.iter().map(Child::id).chain(Some(std::process::id())) {
+            system.refresh_process_specifics(Pid::from(pid as usize), sampling_refresh_kind());
+        }
+        (system, children)
+    }
+
+    fn kill_all(children: Vec<Child>) {
+        for mut child in children {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+
+    fn run(system: &System, query: ProcessQuery) -> Result<ProcessPage, String> {
+        query_processes(system, &query, &[], &HashMap::new(), &AccountCache::new())
+    }
+
+    fn pids(page: &ProcessPage) -> Vec<u32> {
+        page.processes.iter().map(|p| p.pid).collect()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn filters_by_name_and_user() {
+        let (system, children) = process_table(1);
+        let child = children[0].id();
+
+        let by_name = run(&system, ProcessQuery { name: Some("SLEEP".to_string()), ..ProcessQuery::default() }).unwrap();
+        assert_eq!(pids(&by_name), vec![child]);
+
+        let by_regex = run(&system, ProcessQuery { name_regex: Some("^sle+p$".to_string()), ..ProcessQuery::default() }).unwrap();
+        assert_eq!(pids(&by_regex), vec![child]);
+
+        let uid = system.process(Pid::from(child as usize)).unwrap().user_id().unwrap().to_string();
+        let by_uid = run(&system, ProcessQuery { user: Some(uid), ..ProcessQuery::default() }).unwrap();
+        assert_eq!(by_uid.total, 2);
+
+        let other_user = run(&system, ProcessQuery { user: Some("no-such-user".to_string()), ..ProcessQuery::default() }).unwrap();
+        assert_eq!(other_user.total, 0);
+
+        kill_all(children);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn sorts_and_paginates() {
+        let (system, children) = process_table(2);
+        let mut all: Vec<u32> = children.iter().map(Child::id).chain(Some(std::process::id())).collect();
+        all.sort_unstable();
+
+        let query = ProcessQuery { sort_by: "pid".to_string(), sort_order: "asc".to_string(), ..ProcessQuery::default() };
+        assert_eq!(pids(&run(&system, query.clone()).unwrap()), all);
+
+        let page = run(&system, ProcessQuery { offset: 1, limit: Some(1), ..query.clone() }).unwrap();
+        assert_eq!(page.total, 3);
+        assert_eq!(page.offset, 1);
+        assert_eq!(pids(&page), vec![all[1]]);
+
+        let desc = run(&system, ProcessQuery { sort_order: "desc".to_string(), ..query }).unwrap();
+        all.reverse();
+        assert_eq!(pids(&desc), all);
+
+        kill_all(children);
+    }
+
+    #[test]
+    fn rejects_invalid_query() {
+        let (system, _) = process_table(0);
+
+        assert!(run(&system, ProcessQuery { name_regex: Some("(".to_string()), ..ProcessQuery::default() }).is_err());
+        assert!(run(&system, ProcessQuery { sort_by: "threads".to_string(), ..ProcessQuery::default() }).is_err());
+        assert!(run(&system, ProcessQuery { sort_order: "up".to_string(), ..ProcessQuery::default() }).is_err());
+    }
+}
@@ -0,0 +1,343 @@
+use std::collections::{BTreeSet, HashSet};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, Process, System};
+use crate::monitor::port::PortMonitor;
+use crate::monitor::process_terminator::{self, TerminateOptions, TerminationResult, TerminationTarget};
+use crate::monitor::process_tree;
+use crate::rules::protection_policy::ProtectionPolicy;
+
+const PORT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct RestartOptions {
+    /// 发送SIGTERM后等待进程自行退出的时间
+    pub grace_period_ms: u64,
+    /// 同时终止子孙进程，避免残留的工作进程继续占用端口
+    pub include_tree: bool,
+    /// 等待原端口被释放的最长时间
+    pub port_release_timeout_ms: u64,
+    /// 重新启动后等待原端口重新监听的最长时间
+    pub port_restore_timeout_ms: u64,
+}
+
+impl Default for RestartOptions {
+    fn default() -> Self {
+        RestartOptions {
+            grace_period_ms: 5000,
+            include_tree: true,
+            port_release_timeout_ms: 10000,
+            port_restore_timeout_ms: 15000,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RestartResult {
+    pub old_pid: u32,
+    pub new_pid: u32,
+    pub cmd: Vec<String>,
+    pub cwd: Option<String>,
+    pub termination: TerminationResult,
+    /// 原进程监听的端口
+    pub ports: Vec<u16>,
+    /// 超时后仍未被释放的端口
+    pub unreleased_ports: Vec<u16>,
+    /// 新进程或其子进程启动后重新监听的端口
+    pub restored_ports: Vec<u16>,
+    /// 等待超时仍未被新进程重新监听的端口，包括被其他进程抢先占用的端口
+    pub missing_ports: Vec<u16>,
+    /// 新进程在等待结束时是否仍在运行
+    pub running: bool,
+    pub elapsed_ms: u64,
+}
+
+/// 重新启动进程所需的上下文：命令行、可执行文件、工作目录和环境变量
+#[derive(Debug, Clone)]
+pub struct LaunchSpec {
+    pub cmd: Vec<String>,
+    /// 进程的可执行文件，命令行被进程改写（如nginx、postgres）时用它启动
+    pub exe: Option<PathBuf>,
+    pub cwd: Option<PathBuf>,
+    pub environ: Vec<String>,
+}
+
+impl LaunchSpec {
+    /// 从进程信息中读取启动上下文，进程需已按 `ProcessRefreshKind::everything()` 刷新
+    pub fn capture(process: &Process) -> Result<Self, String> {
+        if process.cmd().is_empty() {
+            return Err(format!("无法读取进程{}的命令行，可能是内核线程或权限不足", process.pid()));
+        }
+
+        Ok(LaunchSpec {
+            cmd: process.cmd().to_vec(),
+            exe: process.exe().map(Path::to_path_buf),
+            cwd: process.cwd().map(Path::to_path_buf),
+            environ: process.environ().to_vec(),
+        })
+    }
+
+    /// 以分离方式启动新进程，返回新进程的PID
+    ///
+    /// Unix上新进程在独立的会话中运行，不随本程序的终端或进程组信号一起退出。
+    pub fn launch(&self) -> Result<u32, String> {
+        let program = self
+            .program()
+            .ok_or_else(|| format!("找不到进程的可执行文件: {}", self.cmd[0]))?;
+
+        let mut command = Command::new(&program);
+        command
+            .args(&self.cmd[1..])
+            .env_clear()
+            .envs(self.environ.iter().filter_map(|entry| entry.split_once('=')))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+
+        if let Some(cwd) = &self.cwd {
+            command.current_dir(cwd);
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            const DETACHED_PROCESS: u32 = 0x00000008;
+            const CREATE_NEW_PROCESS_GROUP: u32 = 0x00000200;
+
+            command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+
+            // setsid只修改子进程自身的会话，在fork之后、exec之前调用是安全的
+            unsafe {
+                command.pre_exec(|| {
+                    if libc::setsid() == -1 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+        }
+
+        let mut child = command
+            .spawn()
+            .map_err(|e| format!("无法启动进程 {}: {}", program.display(), e))?;
+        let pid = child.id();
+
+        // 由后台线程回收子进程，避免退出后残留僵尸进程
+        thread::spawn(move || {
+            let _ = child.wait();
+        });
+
+        Ok(pid)
+    }
+
+    // 相对路径（如 ./server）相对于原进程的工作目录解析，不含路径分隔符的命令按PATH查找；
+    // 都找不到时说明命令行已被进程改写（如 "nginx: master process ..."），改用可执行文件
+    fn program(&self) -> Option<PathBuf> {
+        let program = Path::new(&self.cmd[0]);
+
+        let resolved = if program.components().count() == 1 {
+            self.find_in_path(program)
+        } else {
+            let path = match &self.cwd {
+                Some(cwd) if program.is_relative() => cwd.join(program),
+                _ => program.to_path_buf(),
+            };
+            Some(path).filter(|path| path.is_file())
+        };
+
+        resolved.or_else(|| self.exe.clone().filter(|exe| exe.is_file()))
+    }
+
+    // 按原进程的PATH查找，原进程没有PATH时使用本程序的PATH
+    fn find_in_path(&self, program: &Path) -> Option<PathBuf> {
+        let path = self
+            .environ
+            .iter()
+            .find_map(|entry| entry.strip_prefix("PATH="))
+            .map(Into::into)
+            .or_else(|| env::var_os("PATH"))?;
+
+        env::split_paths(&path)
+            .map(|dir| dir.join(program))
+            .find(|candidate| candidate.is_file())
+    }
+}
+
+/// 终止进程并以相同的上下文重新启动，等待原端口释放和重新监听
+///
+/// 会阻塞直到各阶段完成或超时，应在独立线程中调用。
+pub fn restart(
+    pid: u32,
+    spec: &LaunchSpec,
+    targets: &[TerminationTarget],
+    ports: &[u16],
+    options: &RestartOptions,
+    policy: ProtectionPolicy,
+) -> Result<RestartResult, String> {
+    let started = Instant::now();
+
+    let terminate_options = TerminateOptions {
+        grace_period_ms: options.grace_period_ms,
+        include_tree: options.include_tree,
+        force: false,
+    };
+    let termination = process_terminator::terminate(pid, targets, &terminate_options);
+    if !termination.survived.is_empty() {
+        return Err(format!("进程{:?}未能终止，已取消重启", termination.survived));
+    }
+
+    let mut monitor = PortMonitor::new().with_policy(policy);
+
+    // 端口未释放时仍然尝试启动，由新进程自行报告绑定失败
+    let unreleased_ports = wait_for_ports(&mut monitor, ports, None, options.port_release_timeout_ms);
+
+    let new_pid = spec.launch()?;
+
+    let missing_ports = wait_for_ports(&mut monitor, ports, Some(new_pid), options.port_restore_timeout_ms);
+    let restored_ports = ports.iter().copied().filter(|port| !missing_ports.contains(port)).collect();
+
+    let mut system = System::new();
+    let running = system.refresh_process(Pid::from(new_pid as usize));
+
+    Ok(RestartResult {
+        old_pid: pid,
+        new_pid,
+        cmd: spec.cmd.clone(),
+        cwd: spec.cwd.as_ref().map(|cwd| cwd.to_string_lossy().to_string()),
+        termination,
+        ports: ports.to_vec(),
+        unreleased_ports,
+        restored_ports,
+        missing_ports,
+        running,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+// 轮询直到端口全部处于期望状态或超时，返回未达到期望状态的端口
+//
+// `owner` 为None时等待端口被释放，否则等待端口由该进程或其子孙进程监听
+fn wait_for_ports(monitor: &mut PortMonitor, ports: &[u16], owner: Option<u32>, timeout_ms: u64) -> Vec<u16> {
+    let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+    let mut pending: Vec<u16> = ports.to_vec();
+
+    while !pending.is_empty() {
+        // 采集失败时视为状态未知，继续等待
+        if let Ok(current) = monitor.get_listening_ports() {
+            let active: BTreeSet<u16> = match owner {
+                Some(pid) => {
+                    let pids: HashSet<u32> = process_tree::collect_subtree_pids(monitor.system(), pid)
+                        .into_iter()
+                        .collect();
+                    current
+                        .iter()
+                        .filter(|port| port.owners.iter().chain(Some(&port.process)).any(|p| pids.contains(&p.pid)))
+                        .map(|port| port.port)
+                        .collect()
+                }
+                None => current.iter().map(|port| port.port).collect(),
+            };
+            pending.retain(|port| active.contains(port) != owner.is_some());
+        }
+
+        if pending.is_empty() || Instant::now() >= deadline {
+            break;
+        }
+
+        thread::sleep(PORT_POLL_INTERVAL);
+    }
+
+    pending
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::net::TcpListener;
+
+    fn spec(cmd: &[&str], exe: Option<&Path>, cwd: Option<&Path>) -> LaunchSpec {
+        LaunchSpec {
+            cmd: cmd.iter().map(|arg| arg.to_string()).collect(),
+            exe: exe.map(Path::to_path_buf),
+            cwd: cwd.map(Path::to_path_buf),
+            environ: vec!["PATH=/usr/bin:/bin".to_string()],
+        }
+    }
+
+    #[test]
+    fn resolves_program_from_path_and_cwd() {
+        let sh = spec(&["sh", "-c", "true"], None, None).program().unwrap();
+        assert!(sh.ends_with("sh") && sh.is_absolute());
+
+        let dir = std::env::temp_dir().join(format!("restarter_cwd_{}", std::process::id()));
+        fs::create_dir_all(dir.join("bin")).unwrap();
+        fs::write(dir.join("bin/server"), "").unwrap();
+
+        let relative = spec(&["./bin/server"], None, Some(&dir)).program().unwrap();
+        assert_eq!(relative, dir.join("./bin/server"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn falls_back_to_exe_when_argv_is_rewritten() {
+        let exe = Path::new("/bin/sh");
+        let rewritten = spec(&["nginx: master process /usr/sbin/nginx -g daemon off;"], Some(exe), None);
+        assert_eq!(rewritten.program().unwrap(), exe);
+
+        let postgres = spec(&["postgres: 14/main: checkpointer"], Some(exe), None);
+        assert_eq!(postgres.program().unwrap(), exe);
+
+        let missing = spec(&["no-such-program-for-restart"], None, None);
+        assert!(missing.program().is_none());
+        assert!(missing.launch().is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn launches_in_new_session() {
+        let pid = spec(&["sleep", "5"], None, None).launch().unwrap();
+
+        // /proc/<pid>/stat 中会话ID为进程名之后的第4个字段
+        let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).unwrap();
+        let (_, rest) = stat.rsplit_once(')').unwrap();
+        let session: u32 = rest.split_whitespace().nth(3).unwrap().parse().unwrap();
+        assert_eq!(session, pid);
+
+        let mut system = System::new();
+        system.refresh_process(Pid::from(pid as usize));
+        system.process(Pid::from(pid as usize)).unwrap().kill();
+    }
+
+    #[test]
+    fn restored_ports_require_new_owner() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let mut monitor = PortMonitor::new();
+
+        // 监听者是本进程而不是新启动的进程时，端口不算恢复
+        let missing = wait_for_ports(&mut monitor, &[port], Some(u32::MAX), 0);
+        assert_eq!(missing, vec![port]);
+
+        let missing = wait_for_ports(&mut monitor, &[port], Some(std::process::id()), 0);
+        assert!(missing.is_empty());
+
+        let unreleased = wait_for_ports(&mut monitor, &[port], None, 0);
+        assert_eq!(unreleased, vec![port]);
+
+        drop(listener);
+        let unreleased = wait_for_ports(&mut monitor, &[port], None, 2000);
+        assert!(unreleased.is_empty());
+    }
+}
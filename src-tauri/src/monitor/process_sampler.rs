@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+use sysinfo::{Pid, ProcessRefreshKind, RefreshKind, System, ThreadKind, UpdateKind};
+use crate::monitor::process_control;
+use crate::monitor::process_history::{self, ProcessHistory, MAX_SAMPLES};
+
+pub const DEFAULT_SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 周期刷新使用的刷新项
+///
+/// sysinfo默认的刷新不读取命令行和用户，之后启动的进程会缺少这些信息；
+/// 它们在进程生命周期内不会变化，只在首次出现时读取一次。
+pub fn sampling_refresh_kind() -> ProcessRefreshKind {
+    ProcessRefreshKind::new()
+        .with_cpu()
+        .with_memory()
+        .with_disk_usage()
+        .with_exe(UpdateKind::OnlyIfNotSet)
+        .with_cmd(UpdateKind::OnlyIfNotSet)
+        .with_user(UpdateKind::OnlyIfNotSet)
+}
+
+/// 长期存活的进程采样器，放在Tauri托管状态中供各命令共享
+///
+/// sysinfo的CPU占用率需要两次刷新之间的差值计算，每次新建System只会得到0，
+/// 因此由后台线程按固定间隔刷新，命令只读取缓存的快照。每次刷新后同时记录资源历史，
+/// 并读取sysinfo不提供的nice值。
+pub struct ProcessSampler {
+    system: Arc<Mutex<System>>,
+    history: Arc<Mutex<ProcessHistory>>,
+    nice_values: Arc<Mutex<HashMap<u32, i32>>>,
+    interval: Duration,
+}
+
+impl ProcessSampler {
+    pub fn spawn(interval: Duration) -> Self {
+        let mut system = System::new_with_specifics(
+            RefreshKind::new().with_processes(ProcessRefreshKind::everything())
+        );
+        system.refresh_processes_specifics(sampling_refresh_kind());
+        let pids: Vec<u32> = system
+            .processes()
+            .iter()
+            .filter(|(_, process)| process.thread_kind() != Some(ThreadKind::Userland))
+            .map(|(pid, _)| pid.as_u32())
+            .collect();
+
+        let nice_values = Arc::new(Mutex::new(read_nice_values(pids)));
+        let system = Arc::new(Mutex::new(system));
+        let history = Arc::new(Mutex::new(ProcessHistory::new(MAX_SAMPLES, interval)));
+        let weak = Arc::downgrade(&system);
+        let thread_history = Arc::clone(&history);
+        let thread_nice_values = Arc::clone(&nice_values);
+
+        thread::spawn(move || loop {
+            thread::sleep(interval);
+
+            // 采样器被丢弃后线程随之退出
+            let system = match weak.upgrade() {
+                Some(system) => system,
+                None => break,
+            };
+
+            let timestamp = chrono::Local::now().timestamp();
+            let mut samples = {
+                let mut system = match system.lock() {
+                    Ok(system) => system,
+                    Err(poisoned) => poisoned.into_inner(),
+                };
+                system.refresh_processes_specifics(sampling_refresh_kind());
+                process_history::collect_samples(&system, timestamp)
+            };
+
+            // 线程数和nice值需要逐个读取 /proc，释放进程快照锁之后再读，不阻塞命令
+            for sample in &mut samples {
+                sample.read_thread_count();
+            }
+            let nice_values = read_nice_values(samples.iter().map(|sample| sample.pid()));
+            *lock(&thread_nice_values) = nice_values;
+
+            let mut history = match thread_history.lock() {
+                Ok(history) => history,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            history.record(samples, timestamp);
+        });
+
+        ProcessSampler {
+            system,
+            history,
+            nice_values,
+            interval,
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// 获取最近一次采样的进程快照
+    pub fn snapshot(&self) -> MutexGuard<'_, System> {
+        match self.system.lock() {
+            Ok(system) => system,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// 获取进程资源历史
+    pub fn history(&self) -> MutexGuard<'_, ProcessHistory> {
+        match self.history.lock() {
+            Ok(history) => history,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// 最近一次采样时各进程的nice值，仅Linux可用
+    pub fn nice_values(&self) -> MutexGuard<'_, HashMap<u32, i32>> {
+        lock(&self.nice_values)
+    }
+
+    /// 获取进程快照，并补充读取快照之后才启动的进程，如刚开始监听端口的服务
+    pub fn snapshot_including(&self, pids: &[u32]) -> MutexGuard<'_, System> {
+        let mut system = self.snapshot();
+        for &pid in pids {
+            let pid = Pid::from(pid as usize);
+            if system.process(pid).is_none() {
+                system.refresh_process_specifics(pid, sampling_refresh_kind());
+            }
+        }
+        system
+    }
+
+    /// 立即刷新单个进程，用于终止进程等需要最新状态的操作，不影响其他进程的CPU采样
+    pub fn refresh_process(&self, pid: u32) -> MutexGuard<'_, System> {
+        self.refresh_nice(pid);
+        let mut system = self.snapshot();
+        system.refresh_process_specifics(Pid::from(pid as usize), sampling_refresh_kind());
+        system
+    }
+
+    /// 刷新单个进程的全部信息，包括默认采样不读取的工作目录、环境变量和用户
+    pub fn refresh_process_details(&self, pid: u32) -> MutexGuard<'_, System> {
+        self.refresh_nice(pid);
+        let mut system = self.snapshot();
+        system.refresh_process_specifics(Pid::from(pid as usize), ProcessRefreshKind::everything());
+        system
+    }
+
+    // 调整优先级后立即读取，不必等到下一次采样
+    fn refresh_nice(&self, pid: u32) {
+        let nice = process_control::read_nice(Pid::from(pid as usize));
+        let mut nice_values = self.nice_values();
+        match nice {
+            Some(nice) => nice_values.insert(pid, nice),
+            None => nice_values.remove(&pid),
+        };
+    }
+}
+
+fn read_nice_values(pids: impl IntoIterator<Item = u32>) -> HashMap<u32, i32> {
+    pids.into_iter()
+        .filter_map(|pid| process_control::read_nice(Pid::from(pid as usize)).map(|nice| (pid, nice)))
+        .collect()
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[cfg(unix)]
+    #[test]
+    fn snapshot_has_cpu_usage_and_details_of_new_processes() {
+        let interval = Duration::from_millis(200);
+        let sampler = ProcessSampler::spawn(interval);
+
+        // 采样器启动之后才出现的进程，由周期刷新补充命令行和用户
+        let mut child = Command::new("sh").args(["-c", "while :; do :; done"]).spawn().unwrap();
+        let pid = Pid::from(child.id() as usize);
+        thread::sleep(interval * 5);
+
+        {
+            let system = sampler.snapshot();
+            let process = system.process(pid).unwrap();
+            assert!(process.cpu_usage() > 0.0);
+            assert!(!process.cmd().is_empty());
+            assert!(process.user_id().is_some());
+        }
+
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
@@ -0,0 +1,232 @@
+use std::thread;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, ProcessStatus, Signal, System};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+// 发送SIGKILL后等待内核回收进程的时间
+const KILL_WAIT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct TerminateOptions {
+    /// 发送SIGTERM后等待进程自行退出的时间
+    pub grace_period_ms: u64,
+    /// 同时终止该进程的全部子孙进程
+    pub include_tree: bool,
+    /// 跳过优雅退出，直接强制终止
+    pub force: bool,
+}
+
+impl Default for TerminateOptions {
+    fn default() -> Self {
+        TerminateOptions {
+            grace_period_ms: 5000,
+            include_tree: false,
+            force: false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TerminationResult {
+    pub pid: u32,
+    /// 本次需要终止的全部进程
+    pub targets: Vec<u32>,
+    pub exited: Vec<u32>,
+    /// 宽限期内没有退出、被升级为强制终止的进程
+    pub forced: Vec<u32>,
+    /// 强制终止后仍然存活的进程，通常是权限不足
+    pub survived: Vec<u32>,
+    pub elapsed_ms: u64,
+}
+
+// PID可能在等待期间被复用，通过启动时间区分
+#[derive(Debug, Clone, Copy)]
+pub struct TerminationTarget {
+    pub pid: u32,
+    pub start_time: u64,
+}
+
+/// 终止一组进程：先请求优雅退出并在宽限期内轮询，仍存活的进程升级为强制终止
+///
+/// `targets` 中父进程应排在子进程之前，避免父进程重新拉起已终止的子进程。
+pub fn terminate(pid: u32, targets: &[TerminationTarget], options: &TerminateOptions) -> TerminationResult {
+    let started = Instant::now();
+    let mut system = System::new();
+    let mut alive: Vec<TerminationTarget> = targets.to_vec();
+
+    if !options.force {
+        for target in &alive {
+            request_exit(&mut system, target);
+        }
+
+        alive = wait_for_exit(&mut system, alive, Duration::from_millis(options.grace_period_ms));
+    }
+
+    let forced: Vec<u32> = alive.iter().map(|t| t.pid).collect();
+    if !alive.is_empty() {
+        for target in &alive {
+            if refresh_target(&mut system, target) {
+                if let Some(process) = system.process(Pid::from(target.pid as usize)) {
+                    process.kill();
+                }
+            }
+        }
+
+        alive = wait_for_exit(&mut system, alive, KILL_WAIT);
+    }
+
+    let survived: Vec<u32> = alive.iter().map(|t| t.pid).collect();
+    let exited = targets
+        .iter()
+        .map(|t| t.pid)
+        .filter(|pid| !survived.contains(pid))
+        .collect();
+
+    TerminationResult {
+        pid,
+        targets: targets.iter().map(|t| t.pid).collect(),
+        exited,
+        forced,
+        survived,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+fn request_exit(system: &mut System, target: &TerminationTarget) {
+    if !refresh_target(system, target) {
+        return;
+    }
+
+    let process = match system.process(Pid::from(target.pid as usize)) {
+        Some(process) => process,
+        None => return,
+    };
+
+    // Windows不支持SIGTERM，使用不带 /F 的taskkill请求窗口程序关闭
+    if process.kill_with(Signal::Term).is_none() {
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+            let _ = std::process::Command::new("taskkill")
+                .args(&["/PID", &target.pid.to_string()])
+                .creation_flags(CREATE_NO_WINDOW) // 隐藏窗口
+                .output();
+        }
+    }
+}
+
+// 轮询直到全部进程退出或超时，返回仍存活的进程
+fn wait_for_exit(
+    system: &mut System,
+    mut alive: Vec<TerminationTarget>,
+    timeout: Duration,
+) -> Vec<TerminationTarget> {
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        alive.retain(|target| refresh_target(system, target));
+
+        if alive.is_empty() || Instant::now() >= deadline {
+            return alive;
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// 刷新目标进程，返回其是否仍在运行；僵尸进程和PID已被复用的视为已退出
+pub fn refresh_target(system: &mut System, target: &TerminationTarget) -> bool {
+    let pid = Pid::from(target.pid as usize);
+
+    if !system.refresh_process(pid) {
+        return false;
+    }
+
+    match system.process(pid) {
+        Some(process) => process.status() != ProcessStatus::Zombie && process.start_time() == target.start_time,
+        None => false,
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use std::process::{Child, Command};
+
+    fn spawn(program: &str, args: &[&str]) -> (Child, TerminationTarget) {
+        let child = Command::new(program).args(args).spawn().unwrap();
+        let pid = child.id();
+
+        let mut system = System::new();
+        system.refresh_process(Pid::from(pid as usize));
+        let start_time = system.process(Pid::from(pid as usize)).unwrap().start_time();
+
+        (child, TerminationTarget { pid, start_time })
+    }
+
+    fn options(grace_period_ms: u64, force: bool) -> TerminateOptions {
+        TerminateOptions {
+            grace_period_ms,
+            include_tree: false,
+            force,
+        }
+    }
+
+    #[test]
+    fn exits_gracefully() {
+        let (mut child, target) = spawn("sleep", &["30"]);
+
+        let result = terminate(target.pid, &[target], &options(5000, false));
+        assert_eq!(result.exited, vec![target.pid]);
+        assert!(result.forced.is_empty() && result.survived.is_empty());
+        assert!(result.elapsed_ms < 5000);
+
+        let _ = child.wait();
+    }
+
+    #[test]
+    fn force_kills_after_grace_period() {
+        let (mut child, target) = spawn("sh", &["-c", "trap \"\" TERM; sleep 30"]);
+        // 等待shell设置好忽略SIGTERM
+        thread::sleep(Duration::from_millis(300));
+
+        let result = terminate(target.pid, &[target], &options(500, false));
+        assert_eq!(result.forced, vec![target.pid]);
+        assert_eq!(result.exited, vec![target.pid]);
+        assert!(result.survived.is_empty());
+        assert!(result.elapsed_ms >= 500);
+
+        let _ = child.wait();
+    }
+
+    #[test]
+    fn force_skips_grace_period() {
+        let (mut child, target) = spawn("sleep", &["30"]);
+
+        let result = terminate(target.pid, &[target], &options(60_000, true));
+        assert_eq!(result.forced, vec![target.pid]);
+        assert_eq!(result.exited, vec![target.pid]);
+        assert!(result.elapsed_ms < 60_000);
+
+        let _ = child.wait();
+    }
+
+    #[test]
+    fn already_exited_process_is_reported_as_exited() {
+        let (mut child, target) = spawn("sleep", &["30"]);
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        let result = terminate(target.pid, &[target], &options(5000, false));
+        assert_eq!(result.exited, vec![target.pid]);
+        assert!(result.forced.is_empty() && result.survived.is_empty());
+
+        // PID被复用时启动时间不同，同样视为已退出
+        let reused = TerminationTarget { pid: std::process::id(), start_time: 0 };
+        assert!(!refresh_target(&mut System::new(), &reused));
+    }
+}
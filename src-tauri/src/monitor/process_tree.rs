@@ -0,0 +1,205 @@
+use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, Process, System, ThreadKind};
+use crate::commands::process_analyzer::{self, DetailedProcessInfo};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProcessTreeNode {
+    pub process: DetailedProcessInfo,
+    /// 包含自身在内整个子树的CPU和内存占用
+    pub total_cpu_usage: f32,
+    pub total_memory_usage: u64,
+    pub descendant_count: usize,
+    pub children: Vec<ProcessTreeNode>,
+}
+
+/// 根据父进程关系构建进程树，父进程已退出或不可见的进程作为根节点
+///
+/// 指定 `root_pid` 时只返回以该进程为根的子树。同级节点按子树CPU占用降序排列。
+pub fn build_process_tree(
+    system: &System,
+    root_pid: Option<u32>,
+    nice_values: &HashMap<u32, i32>,
+) -> Vec<ProcessTreeNode> {
+    let processes = collect_processes(system);
+    let children = index_children(&processes);
+
+    let roots: Vec<u32> = match root_pid {
+        Some(pid) if processes.contains_key(&pid) => vec![pid],
+        Some(_) => Vec::new(),
+        None => {
+            let mut roots: Vec<u32> = processes
+                .iter()
+                .filter(|(pid, process)| match process.parent() {
+                    Some(parent) => {
+                        let parent = parent.as_u32();
+                        parent == **pid || !processes.contains_key(&parent)
+                    }
+                    None => true,
+                })
+                .map(|(pid, _)| *pid)
+                .collect();
+            roots.sort_unstable();
+            roots
+        }
+    };
+
+    let mut visited = HashSet::new();
+    let mut nodes: Vec<ProcessTreeNode> = roots
+        .into_iter()
+        .filter_map(|pid| build_node(pid, &processes, &children, nice_values, &mut visited))
+        .collect();
+
+    sort_nodes(&mut nodes);
+    nodes
+}
+
+/// 返回以 `pid` 为根的整个子树的PID，父进程排在子进程之前
+pub fn collect_subtree_pids(system: &System, pid: u32) -> Vec<u32> {
+    let processes = collect_processes(system);
+    if !processes.contains_key(&pid) {
+        return Vec::new();
+    }
+
+    let children = index_children(&processes);
+    let mut visited = HashSet::new();
+    let mut pids = Vec::new();
+    let mut queue = vec![pid];
+
+    // 广度优先遍历，保证父进程先于子进程
+    while !queue.is_empty() {
+        let mut next = Vec::new();
+        for pid in queue {
+            if !visited.insert(pid) {
+                continue;
+            }
+
+            pids.push(pid);
+            if let Some(child_pids) = children.get(&pid) {
+                next.extend(child_pids.iter().copied());
+            }
+        }
+        queue = next;
+    }
+
+    pids
+}
+
+// Linux上sysinfo会把用户态线程也列为进程，构建进程树时需要排除，内核线程保留
+fn collect_processes(system: &System) -> HashMap<u32, &Process> {
+    system
+        .processes()
+        .iter()
+        .filter(|(_, process)| process.thread_kind() != Some(ThreadKind::Userland))
+        .map(|(pid, process)| (pid.as_u32(), process))
+        .collect()
+}
+
+fn index_children(processes: &HashMap<u32, &Process>) -> HashMap<u32, Vec<u32>> {
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+
+    for (pid, process) in processes {
+        if let Some(parent) = process.parent() {
+            let parent = parent.as_u32();
+            if parent != *pid && processes.contains_key(&parent) {
+                children.entry(parent).or_default().push(*pid);
+            }
+        }
+    }
+
+    children
+}
+
+fn build_node(
+    pid: u32,
+    processes: &HashMap<u32, &Process>,
+    children: &HashMap<u32, Vec<u32>>,
+    nice_values: &HashMap<u32, i32>,
+    visited: &mut HashSet<u32>,
+) -> Option<ProcessTreeNode> {
+    // PID被复用时父子关系可能成环
+    if !visited.insert(pid) {
+        return None;
+    }
+
+    let process = processes.get(&pid)?;
+    let mut child_nodes: Vec<ProcessTreeNode> = children
+        .get(&pid)
+        .map(|pids| {
+            pids.iter()
+                .filter_map(|child| build_node(*child, processes, children, nice_values, visited))
+                .collect()
+        })
+        .unwrap_or_default();
+    sort_nodes(&mut child_nodes);
+
+    let info = process_analyzer::to_detailed_info(Pid::from(pid as usize), process, nice_values.get(&pid).copied());
+    let total_cpu_usage = info.cpu_usage + child_nodes.iter().map(|c| c.total_cpu_usage).sum::<f32>();
+    let total_memory_usage = info.memory_usage + child_nodes.iter().map(|c| c.total_memory_usage).sum::<u64>();
+    let descendant_count = child_nodes.iter().map(|c| c.descendant_count + 1).sum();
+
+    Some(ProcessTreeNode {
+        process: info,
+        total_cpu_usage,
+        total_memory_usage,
+        descendant_count,
+        children: child_nodes,
+    })
+}
+
+fn sort_nodes(nodes: &mut [ProcessTreeNode]) {
+    nodes.sort_by(|a, b| {
+        b.total_cpu_usage
+            .partial_cmp(&a.total_cpu_usage)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.process.pid.cmp(&b.process.pid))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::{Child, Command};
+
+    #[cfg(unix)]
+    #[test]
+    fn aggregates_subtree_usage() {
+        let pid = std::process::id();
+        let mut children: Vec<Child> = (0..2)
+            .map(|_| Command::new("sleep").arg("30").spawn().unwrap())
+            .collect();
+
+        let mut system = System::new();
+        system.refresh_process(Pid::from(pid as usize));
+        for child in &children {
+            system.refresh_process(Pid::from(child.id() as usize));
+        }
+
+        let tree = build_process_tree(&system, Some(pid), &HashMap::new());
+        assert_eq!(tree.len(), 1);
+
+        let root = &tree[0];
+        assert_eq!(root.process.pid, pid);
+        assert_eq!(root.descendant_count, 2);
+        assert_eq!(root.children.len(), 2);
+        assert_eq!(
+            root.total_memory_usage,
+            root.process.memory_usage + root.children.iter().map(|c| c.process.memory_usage).sum::<u64>()
+        );
+
+        let subtree = collect_subtree_pids(&system, pid);
+        assert_eq!(subtree.len(), 3);
+        assert_eq!(subtree[0], pid);
+        assert!(children.iter().all(|child| subtree.contains(&child.id())));
+
+        // 父进程不在进程表中时作为根节点
+        let roots = build_process_tree(&system, None, &HashMap::new());
+        assert_eq!(roots.len(), 1);
+        assert!(build_process_tree(&system, Some(u32::MAX), &HashMap::new()).is_empty());
+
+        for child in &mut children {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
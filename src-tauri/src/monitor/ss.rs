@@ -0,0 +1,215 @@
+use std::error::Error;
+use std::process::Command;
+use crate::monitor::port::{self, RawSocket, SocketBackend};
+
+/// 通过iproute2的ss命令采集套接字，适用于没有安装net-tools的精简Linux镜像
+pub struct SsBackend;
+
+impl SsBackend {
+    fn run(&self, args: &str) -> Result<Vec<RawSocket>, Box<dyn Error>> {
+        let output = Command::new("ss").arg(args).output()?;
+
+        if !output.status.success() {
+            return Err(format!("ss执行失败: {}", String::from_utf8_lossy(&output.stderr)).into());
+        }
+
+        Ok(parse_ss_output(&String::from_utf8_lossy(&output.stdout)))
+    }
+}
+
+impl SocketBackend for SsBackend {
+    fn name(&self) -> &'static str {
+        "ss"
+    }
+
+    fn sockets(&self) -> Result<Vec<RawSocket>, Box<dyn Error>> {
+        self.run("-tuanpH")
+    }
+
+    fn listening_sockets(&self) -> Result<Vec<RawSocket>, Box<dyn Error>> {
+        self.run("-tulpnH")
+    }
+}
+
+/// 解析 `ss -tulpnH` / `ss -tuanpH` 的输出，带表头的输出也能处理
+///
+/// 格式: `Netid State Recv-Q Send-Q Local:Port Peer:Port [users:(("name",pid=N,fd=M),...)]`
+pub fn parse_ss_output(output: &str) -> Vec<RawSocket> {
+    output.lines().filter_map(parse_ss_line).collect()
+}
+
+fn parse_ss_line(line: &str) -> Option<RawSocket> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+
+    if parts.len() < 6 {
+        return None;
+    }
+
+    let protocol = match parts[0] {
+        "tcp" => "TCP",
+        "udp" => "UDP",
+        _ => return None,
+    };
+
+    let (local_address, local_port) = parse_ss_endpoint(parts[4])?;
+    // 未连接的套接字远端为 "*:*" 或 "0.0.0.0:*"
+    let remote_address = port::extract_host_from_addr(parts[5]).unwrap_or_else(|| parts[5].to_string());
+    let remote_port = port::extract_port_from_addr(parts[5]).unwrap_or(0);
+
+    // 进程列在没有权限或套接字已脱离进程时为空
+    let pids = parts
+        .get(6..)
+        .map(|rest| parse_ss_users(&rest.join(" ")))
+        .unwrap_or_default();
+
+    Some(RawSocket {
+        protocol: protocol.to_string(),
+        local_address,
+        local_port,
+        remote_address,
+        remote_port,
+        state: port::normalize_state(parts[1]),
+        pids,
+    })
+}
+
+// ss的地址可能带有网卡后缀（127.0.0.53%lo:53、[fe80::1%eth0]:546），
+// 同时监听IPv4和IPv6的套接字显示为 *:80
+fn parse_ss_endpoint(endpoint: &str) -> Option<(String, u16)> {
+    let port = port::extract_port_from_addr(endpoint)?;
+    let host = port::extract_host_from_addr(endpoint)?;
+    let host = host.split('%').next().unwrap_or(&host).to_string();
+
+    let host = if host == "*" { "::".to_string() } else { host };
+
+    Some((host, port))
+}
+
+/// 提取 `users:(("nginx",pid=1235,fd=6),("nginx",pid=1234,fd=6))` 中的全部PID，按出现顺序去重
+pub fn parse_ss_users(field: &str) -> Vec<u32> {
+    let mut pids = Vec::new();
+    let mut rest = field;
+
+    // 逐个解析 ("name",pid=N,fd=M)，进程名中可能含有 "pid=" 或括号，只解析名称结束引号之后的字段
+    while let Some(start) = rest.find("(\"") {
+        let tuple = &rest[start + 2..];
+        let name_end = match tuple.find("\",") {
+            Some(end) => end,
+            None => break,
+        };
+
+        let fields = &tuple[name_end + 2..];
+        let fields_end = fields.find(')').unwrap_or(fields.len());
+        let pid = fields[..fields_end]
+            .split(',')
+            .find_map(|field| field.strip_prefix("pid="))
+            .and_then(|pid| pid.parse::<u32>().ok());
+
+        if let Some(pid) = pid {
+            if !pids.contains(&pid) {
+                pids.push(pid);
+            }
+        }
+
+        rest = &fields[fields_end..];
+    }
+
+    pids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ss -tulpnH
+    const LISTENING: &str = "\
+udp   UNCONN 0      0         127.0.0.53%lo:53        0.0.0.0:*    users:((\"systemd-resolve\",pid=612,fd=13))
+udp   UNCONN 0      0      [fe80::1%eth0]:546           [::]:*
+tcp   LISTEN 0      511          0.0.0.0:80          0.0.0.0:*    users:((\"nginx\",pid=1236,fd=6),(\"nginx\",pid=1235,fd=6),(\"nginx\",pid=1234,fd=6))
+tcp   LISTEN 0      4096       127.0.0.1:5432        0.0.0.0:*    users:((\"postgres\",pid=901,fd=5))
+tcp   LISTEN 0      4096           [::1]:5432           [::]:*    users:((\"postgres\",pid=901,fd=6))
+tcp   LISTEN 0      128                *:8080              *:*    users:((\"java\",pid=2001,fd=30),(\"java\",pid=2001,fd=31))
+tcp   LISTEN 0      128          0.0.0.0:22          0.0.0.0:*
+";
+
+    // ss -tuanp，带表头
+    const ALL: &str = "\
+Netid State     Recv-Q Send-Q   Local Address:Port     Peer Address:Port  Process
+tcp   ESTAB     0      0            127.0.0.1:5432        127.0.0.1:53712 users:((\"postgres\",pid=3301,fd=9))
+tcp   TIME-WAIT 0      0            127.0.0.1:53700       127.0.0.1:5432
+tcp   SYN-SENT  0      1          192.168.1.10:40122   93.184.216.34:443   users:((\"curl\",pid=4100,fd=5))
+";
+
+    #[test]
+    fn parses_listening_sockets() {
+        let sockets = parse_ss_output(LISTENING);
+        assert_eq!(sockets.len(), 7);
+        assert!(sockets.iter().all(|s| s.state == "LISTENING"));
+
+        let dns = &sockets[0];
+        assert_eq!(dns.protocol, "UDP");
+        assert_eq!(dns.local_address, "127.0.0.53");
+        assert_eq!(dns.local_port, 53);
+        assert_eq!(dns.remote_address, "0.0.0.0");
+        assert_eq!(dns.pids, vec![612]);
+
+        let dhcp = &sockets[1];
+        assert_eq!(dhcp.local_address, "fe80::1");
+        assert_eq!(dhcp.local_port, 546);
+        assert!(dhcp.pids.is_empty());
+
+        assert_eq!(sockets[4].local_address, "::1");
+        assert_eq!(sockets[4].remote_address, "::");
+
+        // 同时监听IPv4和IPv6
+        assert_eq!(sockets[5].local_address, "::");
+        assert_eq!(sockets[5].local_port, 8080);
+        assert_eq!(sockets[5].remote_port, 0);
+
+        // 没有权限查看所属进程
+        assert!(sockets[6].pids.is_empty());
+    }
+
+    #[test]
+    fn keeps_every_owner_of_shared_socket() {
+        let sockets = parse_ss_output(LISTENING);
+        assert_eq!(sockets[2].pids, vec![1236, 1235, 1234]);
+        // 同一进程的多个描述符只记录一次
+        assert_eq!(sockets[5].pids, vec![2001]);
+    }
+
+    #[test]
+    fn parses_connections_and_skips_header() {
+        let sockets = parse_ss_output(ALL);
+        assert_eq!(sockets.len(), 3);
+
+        assert_eq!(sockets[0].state, "ESTABLISHED");
+        assert_eq!(sockets[0].remote_port, 53712);
+        assert_eq!(sockets[0].pids, vec![3301]);
+
+        assert_eq!(sockets[1].state, "TIME_WAIT");
+        assert!(sockets[1].pids.is_empty());
+
+        assert_eq!(sockets[2].state, "SYN_SENT");
+        assert_eq!(sockets[2].remote_address, "93.184.216.34");
+    }
+
+    #[test]
+    fn parses_users_field() {
+        assert_eq!(
+            parse_ss_users("users:((\"nginx\",pid=1235,fd=6),(\"nginx\",pid=1234,fd=6),(\"nginx\",pid=1235,fd=7))"),
+            vec![1235, 1234]
+        );
+        // 只解析名称之后的字段，进程名中的 "pid=<数字>" 和括号不会被当作PID
+        assert_eq!(parse_ss_users("users:((\"pid=x\",pid=7,fd=3))"), vec![7]);
+        assert_eq!(parse_ss_users("users:((\"a(pid=99)\",pid=7,fd=3),(\"pid=12\",pid=8,fd=4))"), vec![7, 8]);
+        assert!(parse_ss_users("").is_empty());
+        assert!(parse_ss_users("users:((\"init\",pid=,fd=3))").is_empty());
+    }
+
+    #[test]
+    fn ignores_other_socket_families() {
+        let output = "u_str ESTAB 0 0 /run/systemd/journal/stdout 21520 * 21519\nraw UNCONN 0 0 *:58 *:*\n";
+        assert!(parse_ss_output(output).is_empty());
+    }
+}
@@ -1,2 +1,3 @@
 pub mod process_rules;
-pub mod project_detector;
\ No newline at end of file
+pub mod project_detector;
+pub mod protection_policy;
\ No newline at end of file
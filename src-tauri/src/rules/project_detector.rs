@@ -10,6 +10,39 @@ pub struct ProjectSignature {
     pub process_names: Vec<String>,
     pub command_patterns: Vec<String>,
     pub port_ranges: Option<Vec<(u16, u16)>>,
+    pub health_probe: Option<HealthProbe>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ProbeKind {
+    Tcp,
+    Http,
+}
+
+/// 健康检查定义：TCP仅检查能否建立连接，HTTP会请求指定路径并检查状态码
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthProbe {
+    pub kind: ProbeKind,
+    pub path: Option<String>,
+    pub timeout_ms: u64,
+}
+
+impl HealthProbe {
+    pub fn tcp() -> Self {
+        HealthProbe {
+            kind: ProbeKind::Tcp,
+            path: None,
+            timeout_ms: 1000,
+        }
+    }
+
+    pub fn http(path: &str) -> Self {
+        HealthProbe {
+            kind: ProbeKind::Http,
+            path: Some(path.to_string()),
+            timeout_ms: 2000,
+        }
+    }
 }
 
 pub fn detect_project(process: &ProcessInfo, port: u16) -> Option<ProjectInfo> {
@@ -147,7 +180,19 @@ fn detect_generic_project(process: &ProcessInfo, _port: u16) -> Option<ProjectIn
     }
 }
 
-fn get_project_signatures() -> Vec<ProjectSignature> {
+/// 根据项目类型选择健康检查方式，签名中定义了检查方式的按定义执行，其他使用TCP连接检查
+pub fn get_health_probe(project_type: Option<&str>) -> HealthProbe {
+    project_type
+        .and_then(|project_type| {
+            get_project_signatures()
+                .into_iter()
+                .find(|s| s.project_type == project_type)
+        })
+        .and_then(|s| s.health_probe)
+        .unwrap_or_else(HealthProbe::tcp)
+}
+
+pub fn get_project_signatures() -> Vec<ProjectSignature> {
     vec![
         // React项目
         ProjectSignature {
@@ -157,6 +202,7 @@ fn get_project_signatures() -> Vec<ProjectSignature> {
             process_names: vec!["node".to_string()],
             command_patterns: vec!["react-scripts".to_string(), "webpack".to_string()],
             port_ranges: Some(vec![(3000, 3999)]),
+            health_probe: Some(HealthProbe::http("/")),
         },
         
         // Vue项目
@@ -167,6 +213,7 @@ fn get_project_signatures() -> Vec<ProjectSignature> {
             process_names: vec!["node".to_string()],
             command_patterns: vec!["vue-cli".to_string(), "vite".to_string()],
             port_ranges: Some(vec![(5173, 5173), (8080, 8080)]),
+            health_probe: Some(HealthProbe::http("/")),
         },
         
         // Next.js项目
@@ -177,6 +224,7 @@ fn get_project_signatures() -> Vec<ProjectSignature> {
             process_names: vec!["node".to_string()],
             command_patterns: vec!["next".to_string()],
             port_ranges: Some(vec![(3000, 3000)]),
+            health_probe: Some(HealthProbe::http("/")),
         },
         
         // Spring Boot项目
@@ -187,6 +235,7 @@ fn get_project_signatures() -> Vec<ProjectSignature> {
             process_names: vec!["java".to_string()],
             command_patterns: vec!["spring-boot".to_string()],
             port_ranges: Some(vec![(8080, 8080)]),
+            health_probe: Some(HealthProbe::http("/actuator/health")),
         },
         
         // Flask项目
@@ -197,6 +246,7 @@ fn get_project_signatures() -> Vec<ProjectSignature> {
             process_names: vec!["python".to_string()],
             command_patterns: vec!["flask".to_string()],
             port_ranges: Some(vec![(5000, 5000)]),
+            health_probe: Some(HealthProbe::http("/")),
         },
         
         // Django项目
@@ -207,6 +257,7 @@ fn get_project_signatures() -> Vec<ProjectSignature> {
             process_names: vec!["python".to_string()],
             command_patterns: vec!["django".to_string(), "runserver".to_string()],
             port_ranges: Some(vec![(8000, 8000)]),
+            health_probe: Some(HealthProbe::http("/")),
         },
     ]
 }
\ No newline at end of file
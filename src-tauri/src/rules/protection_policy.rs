@@ -0,0 +1,593 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, Process, System, ThreadKind, Uid, Users};
+
+/// 命中规则后的处理方式，按严格程度从低到高排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ProtectionSeverity {
+    /// 允许操作，但提示风险
+    Warn,
+    /// 需要携带确认令牌才能操作
+    Confirm,
+    /// 禁止操作
+    Block,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ProtectionMatcher {
+    /// 进程名，不区分大小写
+    Name(Vec<String>),
+    /// 可执行文件路径前缀
+    ExePrefix(Vec<String>),
+    Pid(Vec<u32>),
+    /// Linux内核线程
+    KernelThread,
+    /// 用户名或用户ID（Windows上为SID）
+    User(Vec<String>),
+    /// 祖先进程名，用于保护某个服务派生出的全部进程
+    Ancestor(Vec<String>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtectionRule {
+    pub id: String,
+    pub description: String,
+    pub matcher: ProtectionMatcher,
+    pub severity: ProtectionSeverity,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtectionPolicy {
+    pub rules: Vec<ProtectionRule>,
+    /// 按用户匹配的规则中用户名对应的用户ID，构造策略时解析一次
+    #[serde(skip)]
+    user_ids: HashMap<String, Uid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtectionDecision {
+    pub pid: u32,
+    pub action: String,
+    /// 命中规则中最严格的处理方式，未命中任何规则时为None
+    pub severity: Option<ProtectionSeverity>,
+    pub matched_rules: Vec<ProtectionRule>,
+    /// 需要确认时，携带此令牌重新发起操作
+    pub confirmation_token: Option<String>,
+}
+
+impl ProtectionDecision {
+    /// 检查是否允许执行操作，需要确认时校验令牌
+    pub fn enforce(&self, confirm_token: Option<&str>) -> Result<(), String> {
+        let reasons = || {
+            self.matched_rules
+                .iter()
+                .map(|rule| rule.description.as_str())
+                .collect::<Vec<_>>()
+                .join("；")
+        };
+
+        match self.severity {
+            Some(ProtectionSeverity::Block) => Err(format!("进程{}受保护，禁止操作：{}", self.pid, reasons())),
+            Some(ProtectionSeverity::Confirm) if confirm_token != self.confirmation_token.as_deref() => Err(format!(
+                "进程{}受保护，需要确认后才能操作：{}",
+                self.pid,
+                reasons()
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl ProtectionPolicy {
+    /// 使用当前平台的默认规则
+    pub fn new() -> Self {
+        let rules = if cfg!(target_os = "windows") {
+            create_windows_rules()
+        } else {
+            create_unix_rules()
+        };
+
+        ProtectionPolicy::from_rules(rules)
+    }
+
+    pub fn from_rules(rules: Vec<ProtectionRule>) -> Self {
+        let user_ids = resolve_user_ids(&rules);
+        ProtectionPolicy { rules, user_ids }
+    }
+
+    /// 补齐内置规则，同ID的用户规则会被内置规则替换，保证其不被删除或放宽
+    pub fn with_builtin_rules(mut self) -> Self {
+        let mut rules = builtin_rules();
+        self.rules.retain(|rule| !rules.iter().any(|builtin| builtin.id == rule.id));
+        rules.append(&mut self.rules);
+
+        ProtectionPolicy::from_rules(rules)
+    }
+
+    /// 评估对进程执行某个操作（如 "kill"、"suspend"）时命中的保护规则
+    pub fn evaluate(&self, system: &System, pid: u32, action: &str) -> Result<ProtectionDecision, String> {
+        self.evaluate_group(system, pid, &[pid], action)
+    }
+
+    /// 评估同时作用于多个进程的操作（如终止进程树），命中规则合并到 `pid` 的决定中
+    pub fn evaluate_group(
+        &self,
+        system: &System,
+        pid: u32,
+        targets: &[u32],
+        action: &str,
+    ) -> Result<ProtectionDecision, String> {
+        let process = system
+            .process(Pid::from(pid as usize))
+            .ok_or_else(|| "进程不存在".to_string())?;
+
+        let targets: Vec<&Process> = targets
+            .iter()
+            .filter_map(|pid| system.process(Pid::from(*pid as usize)))
+            .collect();
+
+        let matched_rules: Vec<ProtectionRule> = self
+            .rules
+            .iter()
+            .filter(|rule| {
+                targets
+                    .iter()
+                    .any(|target| rule_matches(&rule.matcher, system, target, &self.user_ids))
+            })
+            .cloned()
+            .collect();
+
+        let severity = matched_rules.iter().map(|rule| rule.severity).max();
+        let confirmation_token = if severity == Some(ProtectionSeverity::Confirm) {
+            Some(confirmation_token(pid, process.start_time(), action))
+        } else {
+            None
+        };
+
+        Ok(ProtectionDecision {
+            pid,
+            action: action.to_string(),
+            severity,
+            matched_rules,
+            confirmation_token,
+        })
+    }
+}
+
+fn rule_matches(
+    matcher: &ProtectionMatcher,
+    system: &System,
+    process: &Process,
+    user_ids: &HashMap<String, Uid>,
+) -> bool {
+    match matcher {
+        ProtectionMatcher::Name(names) => name_in(process.name(), names),
+        ProtectionMatcher::ExePrefix(prefixes) => process.exe().map_or(false, |exe| {
+            let exe = exe.to_string_lossy();
+            prefixes.iter().any(|prefix| exe.starts_with(prefix.as_str()))
+        }),
+        ProtectionMatcher::Pid(pids) => pids.contains(&process.pid().as_u32()),
+        ProtectionMatcher::KernelThread => process.thread_kind() == Some(ThreadKind::Kernel),
+        ProtectionMatcher::User(names) => process.user_id().map_or(false, |uid| {
+            names.iter().any(|name| {
+                name.parse::<Uid>().map_or(false, |query_uid| &query_uid == uid) || user_ids.get(name) == Some(uid)
+            })
+        }),
+        ProtectionMatcher::Ancestor(names) => {
+            let mut parent = process.parent();
+            // 限制深度，防止PID复用导致父子关系成环
+            for _ in 0..64 {
+                let ancestor = match parent.and_then(|pid| system.process(pid)) {
+                    Some(ancestor) => ancestor,
+                    None => return false,
+                };
+
+                if name_in(ancestor.name(), names) {
+                    return true;
+                }
+                parent = ancestor.parent();
+            }
+            false
+        }
+    }
+}
+
+// 用户名在加载策略时解析，之后新建的用户需要重新保存或加载策略才能按名称匹配
+fn resolve_user_ids(rules: &[ProtectionRule]) -> HashMap<String, Uid> {
+    let names: Vec<&String> = rules
+        .iter()
+        .filter_map(|rule| match &rule.matcher {
+            ProtectionMatcher::User(names) => Some(names),
+            _ => None,
+        })
+        .flatten()
+        .filter(|name| name.parse::<Uid>().is_err())
+        .collect();
+
+    if names.is_empty() {
+        return HashMap::new();
+    }
+
+    Users::new_with_refreshed_list()
+        .list()
+        .iter()
+        .filter(|user| names.iter().any(|name| name.as_str() == user.name()))
+        .map(|user| (user.name().to_string(), user.id().clone()))
+        .collect()
+}
+
+fn name_in(name: &str, names: &[String]) -> bool {
+    names.iter().any(|n| n.eq_ignore_ascii_case(name))
+}
+
+// 令牌与进程实例和操作绑定，进程重启或换一种操作后需要重新确认
+fn confirmation_token(pid: u32, start_time: u64, action: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    (pid, start_time, action).hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// 保护策略配置，保存在应用数据目录下的JSON文件中
+pub struct ProtectionPolicyStore {
+    path: PathBuf,
+    policy: Mutex<ProtectionPolicy>,
+}
+
+impl ProtectionPolicyStore {
+    pub fn load(path: PathBuf) -> Self {
+        // 配置文件不存在或内容损坏时使用默认规则，避免失去保护
+        let policy = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_else(ProtectionPolicy::new);
+
+        ProtectionPolicyStore {
+            path,
+            // 反序列化得到的策略不含用户ID，由 `with_builtin_rules` 重新解析
+            policy: Mutex::new(policy.with_builtin_rules()),
+        }
+    }
+
+    pub fn policy(&self) -> ProtectionPolicy {
+        self.policy.lock().unwrap().clone()
+    }
+
+    /// 保存用户配置的规则，内置规则总是保留
+    pub fn set_policy(&self, policy: ProtectionPolicy) -> Result<(), String> {
+        let policy = policy.with_builtin_rules();
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("无法创建配置目录: {}", e))?;
+        }
+
+        let content = serde_json::to_string_pretty(&policy).map_err(|e| e.to_string())?;
+        fs::write(&self.path, content).map_err(|e| format!("无法保存保护策略: {}", e))?;
+
+        *self.policy.lock().unwrap() = policy;
+        Ok(())
+    }
+
+    pub fn evaluate(&self, system: &System, pid: u32, action: &str) -> Result<ProtectionDecision, String> {
+        self.policy.lock().unwrap().evaluate(system, pid, action)
+    }
+
+    pub fn evaluate_group(
+        &self,
+        system: &System,
+        pid: u32,
+        targets: &[u32],
+        action: &str,
+    ) -> Result<ProtectionDecision, String> {
+        self.policy.lock().unwrap().evaluate_group(system, pid, targets, action)
+    }
+}
+
+fn rule(id: &str, description: &str, matcher: ProtectionMatcher, severity: ProtectionSeverity) -> ProtectionRule {
+    ProtectionRule {
+        id: id.to_string(),
+        description: description.to_string(),
+        matcher,
+        severity,
+    }
+}
+
+fn names(list: &[&str]) -> Vec<String> {
+    list.iter().map(|name| name.to_string()).collect()
+}
+
+// 终止后会导致系统崩溃的进程，用户配置无法删除
+fn builtin_rules() -> Vec<ProtectionRule> {
+    let ids: &[&str] = if cfg!(target_os = "windows") {
+        &["system-idle"]
+    } else {
+        &["init", "kernel-threads"]
+    };
+
+    ProtectionPolicy::new()
+        .rules
+        .into_iter()
+        .filter(|rule| ids.contains(&rule.id.as_str()))
+        .collect()
+}
+
+fn create_unix_rules() -> Vec<ProtectionRule> {
+    vec![
+        rule(
+            "init",
+            "PID 1是init进程，终止会导致系统崩溃",
+            ProtectionMatcher::Pid(vec![1]),
+            ProtectionSeverity::Block,
+        ),
+        rule(
+            "kernel-threads",
+            "内核线程无法也不应被用户态操作",
+            ProtectionMatcher::KernelThread,
+            ProtectionSeverity::Block,
+        ),
+        rule(
+            "core-daemons",
+            "系统核心守护进程",
+            ProtectionMatcher::Name(names(&[
+                "systemd", "systemd-journald", "systemd-logind", "systemd-udevd",
+                "systemd-networkd", "systemd-resolved", "systemd-timesyncd", "dbus-daemon",
+                "dbus-broker", "polkitd", "udevd", "auditd", "launchd", "kernel_task",
+            ])),
+            ProtectionSeverity::Block,
+        ),
+        rule(
+            "systemd-binaries",
+            "systemd组件",
+            ProtectionMatcher::ExePrefix(names(&["/usr/lib/systemd/", "/lib/systemd/"])),
+            ProtectionSeverity::Block,
+        ),
+        rule(
+            "remote-access",
+            "终止SSH服务可能导致无法远程登录",
+            ProtectionMatcher::Name(names(&["sshd"])),
+            ProtectionSeverity::Confirm,
+        ),
+        rule(
+            "container-runtime",
+            "容器运行时，终止会停止其上的全部容器",
+            ProtectionMatcher::Name(names(&["dockerd", "containerd", "kubelet", "crio"])),
+            ProtectionSeverity::Confirm,
+        ),
+        rule(
+            "desktop-session",
+            "图形会话组件，终止会导致桌面注销",
+            ProtectionMatcher::Name(names(&[
+                "Xorg", "Xwayland", "gnome-shell", "kwin_x11", "kwin_wayland",
+                "plasmashell", "gdm", "gdm3", "sddm", "lightdm", "WindowServer",
+            ])),
+            ProtectionSeverity::Confirm,
+        ),
+        rule(
+            "network",
+            "网络管理服务，终止可能导致断网",
+            ProtectionMatcher::Name(names(&["NetworkManager", "wpa_supplicant", "dhclient", "networkd-dispatcher"])),
+            ProtectionSeverity::Confirm,
+        ),
+        rule(
+            "container-processes",
+            "容器内的进程，建议通过停止容器来结束",
+            ProtectionMatcher::Ancestor(names(&["containerd-shim", "containerd-shim-runc-v2", "conmon"])),
+            ProtectionSeverity::Warn,
+        ),
+        rule(
+            "root-processes",
+            "以root身份运行的进程",
+            ProtectionMatcher::User(names(&["root"])),
+            ProtectionSeverity::Warn,
+        ),
+    ]
+}
+
+fn create_windows_rules() -> Vec<ProtectionRule> {
+    vec![
+        rule(
+            "system-idle",
+            "System和Idle进程",
+            ProtectionMatcher::Pid(vec![0, 4]),
+            ProtectionSeverity::Block,
+        ),
+        rule(
+            "core-processes",
+            "Windows系统关键进程",
+            ProtectionMatcher::Name(names(&[
+                "System", "smss.exe", "csrss.exe", "wininit.exe", "services.exe", "lsass.exe",
+                "winlogon.exe", "svchost.exe", "Registry", "Idle", "Secure System",
+                "Memory Compression", "fontdrvhost.exe", "dwm.exe",
+            ])),
+            ProtectionSeverity::Block,
+        ),
+        rule(
+            "shell",
+            "Windows资源管理器，终止后任务栏和桌面会消失，需要手动重启",
+            ProtectionMatcher::Name(names(&["explorer.exe"])),
+            ProtectionSeverity::Confirm,
+        ),
+        rule(
+            "system-components",
+            "Windows系统组件",
+            ProtectionMatcher::Name(names(&[
+                "spoolsv.exe", "taskhost.exe", "taskhostw.exe", "RuntimeBroker.exe",
+                "ShellExperienceHost.exe", "SearchUI.exe", "sihost.exe", "ctfmon.exe",
+                "conhost.exe", "dllhost.exe",
+            ])),
+            ProtectionSeverity::Confirm,
+        ),
+        rule(
+            "system-directory",
+            "位于系统目录的程序",
+            ProtectionMatcher::ExePrefix(names(&["C:\\Windows\\System32\\", "C:\\Windows\\SysWOW64\\"])),
+            ProtectionSeverity::Warn,
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::{Child, Command};
+    use sysinfo::{ProcessRefreshKind, UpdateKind};
+
+    // 本进程及其子进程组成的进程表，读取用户和可执行文件用于匹配
+    fn process_table(children: &[&Child]) -> System {
+        let refresh_kind = ProcessRefreshKind::new()
+            .with_exe(UpdateKind::OnlyIfNotSet)
+            .with_user(UpdateKind::OnlyIfNotSet);
+
+        let mut system = System::new();
+        for pid in children.iter().map(|child| child.id()).chain(Some(std::process::id())) {
+            system.refresh_process_specifics(Pid::from(pid as usize), refresh_kind);
+        }
+        system
+    }
+
+    fn policy(rules: Vec<ProtectionRule>) -> ProtectionPolicy {
+        ProtectionPolicy::from_rules(rules)
+    }
+
+    fn matched_ids(decision: &ProtectionDecision) -> Vec<&str> {
+        decision.matched_rules.iter().map(|rule| rule.id.as_str()).collect()
+    }
+
+    #[test]
+    fn matches_name_exe_and_user() {
+        let pid = std::process::id();
+        let system = process_table(&[]);
+        let process = system.process(Pid::from(pid as usize)).unwrap();
+
+        let exe = process.exe().unwrap().to_string_lossy().to_string();
+        let uid = process.user_id().unwrap().to_string();
+        let rules = policy(vec![
+            rule("name", "按名称", ProtectionMatcher::Name(vec![process.name().to_uppercase()]), ProtectionSeverity::Warn),
+            rule("exe", "按路径", ProtectionMatcher::ExePrefix(vec![exe]), ProtectionSeverity::Warn),
+            rule("user", "按用户", ProtectionMatcher::User(vec![uid]), ProtectionSeverity::Warn),
+            rule("pid", "按PID", ProtectionMatcher::Pid(vec![pid]), ProtectionSeverity::Warn),
+            rule("other", "其他进程", ProtectionMatcher::Name(names(&["no-such-process"])), ProtectionSeverity::Block),
+        ]);
+
+        let decision = rules.evaluate(&system, pid, "kill").unwrap();
+        assert_eq!(matched_ids(&decision), vec!["name", "exe", "user", "pid"]);
+        assert_eq!(decision.severity, Some(ProtectionSeverity::Warn));
+        assert!(decision.enforce(None).is_ok());
+
+        assert!(rules.evaluate(&system, u32::MAX, "kill").is_err());
+    }
+
+    #[test]
+    fn resolves_user_names_when_policy_is_built() {
+        let pid = std::process::id();
+        let system = process_table(&[]);
+        let uid = system.process(Pid::from(pid as usize)).unwrap().user_id().unwrap().clone();
+        let users = Users::new_with_refreshed_list();
+        let name = users.get_user_by_id(&uid).unwrap().name().to_string();
+
+        let rules = policy(vec![rule("user", "按用户名", ProtectionMatcher::User(vec![name.clone()]), ProtectionSeverity::Warn)]);
+        assert_eq!(rules.user_ids.get(&name), Some(&uid));
+        assert_eq!(matched_ids(&rules.evaluate(&system, pid, "kill").unwrap()), vec!["user"]);
+
+        // 反序列化得到的策略经过 `with_builtin_rules` 后同样可以按用户名匹配
+        let restored: ProtectionPolicy = serde_json::from_str(&serde_json::to_string(&rules).unwrap()).unwrap();
+        assert!(restored.user_ids.is_empty());
+        let decision = restored.with_builtin_rules().evaluate(&system, pid, "kill").unwrap();
+        assert!(matched_ids(&decision).contains(&"user"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn matches_ancestors_and_groups() {
+        let pid = std::process::id();
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let system = process_table(&[&child]);
+        let name = system.process(Pid::from(pid as usize)).unwrap().name().to_string();
+
+        let rules = policy(vec![rule(
+            "ancestor",
+            "测试进程派生",
+            ProtectionMatcher::Ancestor(vec![name]),
+            ProtectionSeverity::Block,
+        )]);
+
+        let decision = rules.evaluate(&system, child.id(), "kill").unwrap();
+        assert_eq!(matched_ids(&decision), vec!["ancestor"]);
+        assert!(rules.evaluate(&system, pid, "kill").unwrap().severity.is_none());
+
+        // 终止进程树时子进程命中的规则合并到根进程的决定中
+        let group = rules.evaluate_group(&system, pid, &[pid, child.id()], "kill_tree").unwrap();
+        assert_eq!(group.severity, Some(ProtectionSeverity::Block));
+        assert!(group.enforce(None).is_err());
+
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
+    #[test]
+    fn strictest_rule_wins() {
+        let pid = std::process::id();
+        let system = process_table(&[]);
+        let rules = policy(vec![
+            rule("warn", "提示", ProtectionMatcher::Pid(vec![pid]), ProtectionSeverity::Warn),
+            rule("block", "禁止", ProtectionMatcher::Pid(vec![pid]), ProtectionSeverity::Block),
+            rule("confirm", "确认", ProtectionMatcher::Pid(vec![pid]), ProtectionSeverity::Confirm),
+        ]);
+
+        let decision = rules.evaluate(&system, pid, "kill").unwrap();
+        assert_eq!(decision.severity, Some(ProtectionSeverity::Block));
+        assert!(decision.confirmation_token.is_none());
+
+        let error = decision.enforce(Some("token")).unwrap_err();
+        assert!(error.contains("提示") && error.contains("禁止") && error.contains("确认"));
+    }
+
+    #[test]
+    fn confirmation_token_is_bound_to_action() {
+        let pid = std::process::id();
+        let system = process_table(&[]);
+        let rules = policy(vec![rule("confirm", "确认", ProtectionMatcher::Pid(vec![pid]), ProtectionSeverity::Confirm)]);
+
+        let kill = rules.evaluate(&system, pid, "kill").unwrap();
+        let token = kill.confirmation_token.clone().unwrap();
+        assert!(kill.enforce(None).is_err());
+        assert!(kill.enforce(Some("wrong")).is_err());
+        assert!(kill.enforce(Some(&token)).is_ok());
+
+        // 同一操作的令牌保持不变，换一种操作需要重新确认
+        assert_eq!(rules.evaluate(&system, pid, "kill").unwrap().confirmation_token, Some(token.clone()));
+        let suspend = rules.evaluate(&system, pid, "suspend").unwrap();
+        assert!(suspend.enforce(Some(&token)).is_err());
+    }
+
+    #[test]
+    fn builtin_rules_cannot_be_removed() {
+        let dir = std::env::temp_dir().join(format!("protection_policy_{}", std::process::id()));
+        let path = dir.join("process_protection.json");
+        let builtin: Vec<String> = builtin_rules().into_iter().map(|rule| rule.id).collect();
+        assert!(!builtin.is_empty());
+
+        let store = ProtectionPolicyStore::load(path.clone());
+        let weakened: Vec<ProtectionRule> = builtin_rules()
+            .into_iter()
+            .map(|rule| ProtectionRule { severity: ProtectionSeverity::Warn, ..rule })
+            .collect();
+        store.set_policy(policy(weakened)).unwrap();
+
+        let saved = store.policy();
+        assert_eq!(saved.rules.len(), builtin.len());
+        assert!(saved.rules.iter().all(|rule| rule.severity == ProtectionSeverity::Block));
+
+        // 手动编辑配置文件删除内置规则后，加载时重新补齐
+        fs::write(&path, r#"{"rules":[]}"#).unwrap();
+        let reloaded = ProtectionPolicyStore::load(path);
+        let ids: Vec<String> = reloaded.policy().rules.into_iter().map(|rule| rule.id).collect();
+        assert_eq!(ids, builtin);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,2 +1,3 @@
 pub mod system_info;
-pub mod process_utils;
\ No newline at end of file
+pub mod process_utils;
+pub mod port_utils;
\ No newline at end of file
@@ -0,0 +1,53 @@
+use std::io::ErrorKind;
+use std::net::{Ipv4Addr, Ipv6Addr, TcpListener, UdpSocket};
+
+/// 通过实际绑定判断TCP端口是否空闲（同时检查IPv4和IPv6通配地址）
+pub fn is_tcp_port_free(port: u16) -> bool {
+    if TcpListener::bind((Ipv4Addr::UNSPECIFIED, port)).is_err() {
+        return false;
+    }
+
+    // 系统未启用IPv6时只看IPv4的结果
+    match TcpListener::bind((Ipv6Addr::UNSPECIFIED, port)) {
+        Ok(_) => true,
+        Err(e) => e.kind() != ErrorKind::AddrInUse,
+    }
+}
+
+/// 通过实际绑定判断UDP端口是否空闲
+pub fn is_udp_port_free(port: u16) -> bool {
+    if UdpSocket::bind((Ipv4Addr::UNSPECIFIED, port)).is_err() {
+        return false;
+    }
+
+    match UdpSocket::bind((Ipv6Addr::UNSPECIFIED, port)) {
+        Ok(_) => true,
+        Err(e) => e.kind() != ErrorKind::AddrInUse,
+    }
+}
+
+/// 按协议判断端口是否空闲，协议为 "TCP"、"UDP" 或 "BOTH"
+pub fn is_port_free(port: u16, protocol: &str) -> bool {
+    match protocol.to_uppercase().as_str() {
+        "UDP" => is_udp_port_free(port),
+        "BOTH" => is_tcp_port_free(port) && is_udp_port_free(port),
+        _ => is_tcp_port_free(port),
+    }
+}
+
+/// 从指定端口向两侧查找按协议空闲的最近端口，距离相同时优先选择较大的端口
+///
+/// 协议取值与 `is_port_free` 相同。
+pub fn find_nearest_free_port(port: u16, protocol: &str, max_distance: u16) -> Option<u16> {
+    for distance in 1..=max_distance {
+        let candidates = [port.checked_add(distance), port.checked_sub(distance)];
+
+        for candidate in candidates.into_iter().flatten() {
+            if candidate != 0 && is_port_free(candidate, protocol) {
+                return Some(candidate);
+            }
+        }
+    }
+
+    None
+}
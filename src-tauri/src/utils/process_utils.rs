@@ -38,36 +38,4 @@ pub fn get_friendly_process_name(process_name: &str) -> String {
         "vlc.exe" => "VLC媒体播放器".to_string(),
         _ => process_name.to_string(),
     }
-}
-
-pub fn is_system_process(process_name: &str) -> bool {
-    let system_processes = [
-        "svchost.exe",
-        "System",
-        "smss.exe",
-        "csrss.exe",
-        "wininit.exe",
-        "services.exe",
-        "lsass.exe",
-        "winlogon.exe",
-        "spoolsv.exe",
-        "explorer.exe",
-        "dwm.exe",
-        "taskhost.exe",
-        "taskhostw.exe",
-        "RuntimeBroker.exe",
-        "ShellExperienceHost.exe",
-        "SearchUI.exe",
-        "sihost.exe",
-        "ctfmon.exe",
-        "conhost.exe",
-        "dllhost.exe",
-        "fontdrvhost.exe",
-        "Registry",
-        "Idle",
-        "Secure System",
-        "Memory Compression",
-    ];
-    
-    system_processes.contains(&process_name)
 }
\ No newline at end of file
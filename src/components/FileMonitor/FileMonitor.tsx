@@ -1,4 +1,4 @@
-import React, { useState } from 'react';
+import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/tauri';
 import { open } from '@tauri-apps/api/dialog';
 import { FileOccupancy } from '../../types/file';
@@ -11,6 +11,20 @@ const FileMonitor: React.FC = () => {
   const [loading, setLoading] = useState<boolean>(false);
   const [error, setError] = useState<string | null>(null);
   const [recentFiles, setRecentFiles] = useState<string[]>([]);
+  const [isSupported, setIsSupported] = useState<boolean>(true);
+
+  useEffect(() => {
+    checkSupport();
+  }, []);
+
+  const checkSupport = async () => {
+    try {
+      const supported = await invoke<boolean>('is_file_monitor_supported');
+      setIsSupported(supported);
+    } catch (err) {
+      console.error('检查文件占用检测支持情况失败:', err);
+    }
+  };
 
   const handleFilePathChange = (e: React.ChangeEvent<HTMLInputElement>) => {
     setFilePath(e.target.value);
@@ -27,7 +41,9 @@ const FileMonitor: React.FC = () => {
       
       if (selected && !Array.isArray(selected)) {
         setFilePath(selected);
-        checkFileOccupancy(selected);
+        if (isSupported) {
+          checkFileOccupancy(selected);
+        }
       }
     } catch (err) {
       console.error('选择文件失败:', err);
@@ -59,20 +75,39 @@ const FileMonitor: React.FC = () => {
 
   const handleSubmit = (e: React.FormEvent) => {
     e.preventDefault();
-    if (filePath) {
+    if (filePath && isSupported) {
       checkFileOccupancy(filePath);
     }
   };
 
   const handleRecentFileClick = (path: string) => {
     setFilePath(path);
-    checkFileOccupancy(path);
+    if (isSupported) {
+      checkFileOccupancy(path);
+    }
   };
 
   return (
     <div className="content-container">
       <h2 className="text-2xl font-bold mb-4">📁 文件占用监控</h2>
       
+      {!isSupported && (
+        <div className="bg-yellow-50 border-l-4 border-yellow-500 p-4 mb-6">
+          <div className="flex">
+            <div className="flex-shrink-0">
+              <svg className="h-5 w-5 text-yellow-400" viewBox="0 0 20 20" fill="currentColor">
+                <path fillRule="evenodd" d="M8.257 3.099c.765-1.36 2.722-1.36 3.486 0l5.58 9.92c.75 1.334-.213 2.98-1.742 2.98H4.42c-1.53 0-2.493-1.646-1.743-2.98l5.58-9.92zM11 13a1 1 0 11-2 0 1 1 0 012 0zm-1-8a1 1 0 00-1 1v3a1 1 0 002 0V6a1 1 0 00-1-1z" clipRule="evenodd" />
+              </svg>
+            </div>
+            <div className="ml-3">
+              <p className="text-sm text-yellow-700">
+                文件占用检测目前仅支持Linux系统。
+              </p>
+            </div>
+          </div>
+        </div>
+      )}
+      
       <div className="file-check-container rounded-lg shadow p-6 mb-6">
         <h3 className="text-lg font-medium mb-4">检查文件占用</h3>
         
@@ -97,7 +132,7 @@ const FileMonitor: React.FC = () => {
           <button
             type="submit"
             className="btn btn-primary w-full"
-            disabled={!filePath || loading}
+            disabled={!filePath || loading || !isSupported}
           >
             {loading ? '检查中...' : '检查文件占用'}
           </button>
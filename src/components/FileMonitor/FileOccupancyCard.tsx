@@ -1,5 +1,6 @@
 import React, { useState } from 'react';
 import { invoke } from '@tauri-apps/api/tauri';
+import { RISK_LEVEL_LABELS } from '../../types/action';
 import { FileOccupancy } from '../../types/file';
 
 interface FileOccupancyCardProps {
@@ -124,10 +125,10 @@ const FileOccupancyCard: React.FC<FileOccupancyCardProps> = ({ fileOccupancy })
                     <p className="text-sm text-gray-500">
                       风险等级：
                       <span className={
-                        solution.risk_level === '高' ? 'text-red-600' : 
-                        solution.risk_level === '中' ? 'text-yellow-600' : 'text-green-600'
+                        solution.risk_level === 'High' ? 'text-red-600' : 
+                        solution.risk_level === 'Medium' ? 'text-yellow-600' : 'text-green-600'
                       }>
-                        {solution.risk_level}
+                        {RISK_LEVEL_LABELS[solution.risk_level]}
                       </span>
                     </p>
                   </li>
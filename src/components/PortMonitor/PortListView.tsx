@@ -99,7 +99,7 @@ const PortListView: React.FC<PortListViewProps> = ({ ports }) => {
           </thead>
           <tbody className="bg-white dark:bg-gray-900 divide-y divide-gray-200 dark:divide-gray-700">
             {ports.map((port) => (
-              <tr key={`${port.protocol}-${port.port}`} className="hover:bg-gray-50 dark:hover:bg-gray-800">
+              <tr key={`${port.protocol}-${port.local_address}-${port.port}`} className="hover:bg-gray-50 dark:hover:bg-gray-800">
                 <td className="px-6 py-4 whitespace-nowrap text-sm font-medium text-gray-900 dark:text-gray-100">
                   {port.port}
                 </td>
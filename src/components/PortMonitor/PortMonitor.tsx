@@ -128,7 +128,7 @@ const PortMonitor: React.FC<PortMonitorProps> = ({ ports, loading, error, onRefr
         viewMode === 'card' ? (
           <div className="grid grid-cols-1 gap-4">
             {filteredPorts.map((port) => (
-              <PortCard key={`${port.protocol}-${port.port}`} port={port} />
+              <PortCard key={`${port.protocol}-${port.local_address}-${port.port}`} port={port} />
             ))}
           </div>
         ) : (
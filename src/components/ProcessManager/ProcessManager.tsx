@@ -257,6 +257,7 @@ function getMockProcesses(): DetailedProcessInfo[] {
       memory_usage: 250 * 1024 * 1024, // 250 MB
       status: "Running",
       start_time: Date.now() - 3600000, // 1小时前
+      stopped: false,
     },
     {
       pid: 2345,
@@ -267,6 +268,7 @@ function getMockProcesses(): DetailedProcessInfo[] {
       memory_usage: 120 * 1024 * 1024, // 120 MB
       status: "Running",
       start_time: Date.now() - 7200000, // 2小时前
+      stopped: false,
     },
     {
       pid: 3456,
@@ -277,6 +279,7 @@ function getMockProcesses(): DetailedProcessInfo[] {
       memory_usage: 80 * 1024 * 1024, // 80 MB
       status: "Running",
       start_time: Date.now() - 86400000, // 1天前
+      stopped: false,
     },
     {
       pid: 4567,
@@ -287,6 +290,7 @@ function getMockProcesses(): DetailedProcessInfo[] {
       memory_usage: 45 * 1024 * 1024, // 45 MB
       status: "Running",
       start_time: Date.now() - 86400000 * 2, // 2天前
+      stopped: false,
     },
     {
       pid: 5678,
@@ -297,6 +301,7 @@ function getMockProcesses(): DetailedProcessInfo[] {
       memory_usage: 320 * 1024 * 1024, // 320 MB
       status: "Running",
       start_time: Date.now() - 10800000, // 3小时前
+      stopped: false,
     },
   ];
 }
@@ -0,0 +1,35 @@
+export type RiskLevel = 'None' | 'Low' | 'Medium' | 'High';
+
+export const RISK_LEVEL_LABELS: Record<RiskLevel, string> = {
+  None: '无',
+  Low: '低',
+  Medium: '中',
+  High: '高',
+};
+
+export type ActionKind =
+  | { StopProcess: { pid: number } }
+  | { ForceKillProcess: { pid: number } }
+  | { KillProcessTree: { pid: number } }
+  | { RestartProcess: { pid: number } }
+  | { ShowProcessDetail: { pid: number } }
+  | { ShowProcessLogs: { pid: number } }
+  | { StopContainer: { container_id: string } }
+  | { RestartContainer: { container_id: string } }
+  | { ShowContainer: { container_id: string } }
+  | { ShowContainerLogs: { container_id: string } }
+  | { StopContainerOnPort: { port: number } }
+  | { ShowContainerOnPort: { port: number } }
+  | { OpenBrowser: { url: string } }
+  | { OpenFolder: { path: string } }
+  | 'OpenTaskManager'
+  | { UseAlternativePort: { port: number } }
+  | { WaitForRelease: { port: number } }
+  | 'Manual';
+
+export interface ActionOutcome {
+  kind: ActionKind;
+  executed: boolean;
+  message: string;
+  data?: unknown;
+}
@@ -0,0 +1,24 @@
+export interface AuditRecord {
+  timestamp: number;
+  actor?: string;
+  action: string;
+  target_pid?: number;
+  target_container?: string;
+  process_name?: string;
+  cmd: string[];
+  success: boolean;
+  error?: string;
+  detail?: string;
+}
+
+export interface AuditQuery {
+  action?: string;
+  actor?: string;
+  pid?: number;
+  container?: string;
+  process_name?: string;
+  success?: boolean;
+  since?: number;
+  until?: number;
+  limit?: number;
+}
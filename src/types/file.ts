@@ -1,6 +1,9 @@
+import { ActionKind, RiskLevel } from './action';
+
 export interface FileOccupancy {
   filePath: string;
   fileName: string;
+  is_directory: boolean;
   occupiedBy: ProcessInfo[];
   reason: string;
   impact: 'Low' | 'Medium' | 'High';
@@ -12,11 +15,26 @@ export interface ProcessInfo {
   name: string;
   friendlyName: string;
   exe_path?: string;
+  access_mode: 'read' | 'write' | 'read_write' | 'mapped' | 'cwd' | 'none';
+  fds: number[];
+  paths: string[];
+  memory_mapped: boolean;
+  uses_as_cwd: boolean;
+  locks: FileLock[];
+}
+
+export interface FileLock {
+  lock_type: 'POSIX' | 'FLOCK' | 'OFDLCK' | 'LEASE';
+  mandatory: boolean;
+  access: string;
+  start: number;
+  end?: number;
 }
 
 export interface Solution {
   action: string;
   description: string;
-  risk_level: string;
+  risk_level: RiskLevel;
+  kind: ActionKind;
   command?: string;
 }
\ No newline at end of file
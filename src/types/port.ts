@@ -1,10 +1,36 @@
+import { ActionKind, RiskLevel } from './action';
+import { DockerContainer } from './docker';
+
+export type SocketBackendName = 'procfs' | 'ss' | 'netstat';
+
 export interface PortInfo {
   port: number;
+  local_address: string;
   protocol: 'TCP' | 'UDP';
   status: 'LISTENING' | 'ESTABLISHED';
   process: ProcessInfo;
+  owners: ProcessInfo[];
   project?: ProjectInfo;
   suggestions: ActionSuggestion[];
+  reservation?: PortReservation;
+  reservation_conflict: boolean;
+  health?: HealthStatus;
+  fingerprint?: ProtocolFingerprint;
+  backend: SocketBackendName;
+}
+
+export interface ProtocolFingerprint {
+  protocol: string;
+  version?: string;
+  detail?: string;
+}
+
+export interface HealthStatus {
+  probe: 'TCP' | 'HTTP';
+  healthy: boolean;
+  latency_ms: number;
+  status_code?: number;
+  error?: string;
 }
 
 export interface ProcessInfo {
@@ -12,6 +38,7 @@ export interface ProcessInfo {
   name: string;
   exe_path?: string;
   cmd: string[];
+  parent_pid?: number;
 }
 
 export interface ProjectInfo {
@@ -24,11 +51,102 @@ export interface ProjectInfo {
 export interface ActionSuggestion {
   action: string;
   description: string;
-  risk_level: string;
+  risk_level: RiskLevel;
+  kind: ActionKind;
 }
 
 export interface PortFilterOptions {
   search: string;
   type?: 'all' | 'development' | 'system' | 'docker';
   status?: 'all' | 'listening' | 'established';
-}
\ No newline at end of file
+}
+
+export interface ConnectionInfo {
+  protocol: 'TCP' | 'UDP';
+  local_address: string;
+  local_port: number;
+  remote_address: string;
+  remote_port: number;
+  state: string;
+  process?: ProcessInfo;
+}
+
+export interface ProcessConnectionStats {
+  pid: number;
+  name: string;
+  total: number;
+  states: Record<string, number>;
+}
+
+export interface ConnectionList {
+  connections: ConnectionInfo[];
+  state_counts: Record<string, number>;
+  process_stats: ProcessConnectionStats[];
+  backend: SocketBackendName;
+}
+
+export interface PortOwner {
+  process: ProcessInfo;
+  project?: ProjectInfo;
+  addresses: string[];
+}
+
+export interface PortDiagnosis {
+  port: number;
+  in_use: boolean;
+  summary: string;
+  sockets: ConnectionInfo[];
+  dual_stack: boolean;
+  owners: PortOwner[];
+  container?: DockerContainer;
+  suggestions: ActionSuggestion[];
+  suggested_port?: number;
+}
+
+export interface PortReservation {
+  port: number;
+  protocol: 'TCP' | 'UDP' | 'BOTH';
+  project: string;
+  path?: string;
+  note?: string;
+  reserved_at: number;
+}
+
+export interface PortOwnerChange {
+  port: PortInfo;
+  previous_process: ProcessInfo;
+}
+
+export interface PortWatcherStatus {
+  running: boolean;
+  recording_history: boolean;
+  interval_ms: number;
+}
+
+export interface PortHistoryRecord {
+  timestamp: number;
+  event: 'opened' | 'closed' | 'owner-changed';
+  port: number;
+  protocol: string;
+  local_address: string;
+  pid: number;
+  process_name: string;
+  cmd: string[];
+  project?: string;
+  project_type?: string;
+  previous_pid?: number;
+  previous_process_name?: string;
+}
+
+export interface PortHistoryQuery {
+  port?: number;
+  process_name?: string;
+  since?: number;
+  until?: number;
+  limit?: number;
+}
+
+export interface PortHistoryRetention {
+  max_records: number;
+  max_age_hours: number;
+}
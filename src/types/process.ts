@@ -7,6 +7,17 @@ export interface DetailedProcessInfo {
   memory_usage: number;
   status: string;
   start_time: number;
+  parent_pid?: number;
+  nice?: number;
+  stopped: boolean;
+}
+
+export interface ProcessTreeNode {
+  process: DetailedProcessInfo;
+  total_cpu_usage: number;
+  total_memory_usage: number;
+  descendant_count: number;
+  children: ProcessTreeNode[];
 }
 
 export interface ProcessCategory {
@@ -14,4 +25,152 @@ export interface ProcessCategory {
   description: string;
   icon: string;
   color: string;
-}
\ No newline at end of file
+}
+export interface ProcessQuery {
+  name?: string;
+  name_regex?: string;
+  user?: string;
+  exe_prefix?: string;
+  min_cpu?: number;
+  min_memory?: number;
+  status?: string;
+  has_listening_port?: boolean;
+  project_type?: string;
+  sort_by?: 'cpu' | 'memory' | 'pid' | 'name' | 'start_time';
+  sort_order?: 'asc' | 'desc';
+  offset?: number;
+  limit?: number;
+}
+
+export interface ProcessPage {
+  total: number;
+  offset: number;
+  processes: DetailedProcessInfo[];
+}
+
+export interface TerminateOptions {
+  grace_period_ms?: number;
+  include_tree?: boolean;
+  force?: boolean;
+}
+
+export interface TerminationResult {
+  pid: number;
+  targets: number[];
+  exited: number[];
+  forced: number[];
+  survived: number[];
+  elapsed_ms: number;
+}
+
+export type IoPriorityClass = 'realtime' | 'best-effort' | 'idle';
+
+export type ProtectionSeverity = 'Warn' | 'Confirm' | 'Block';
+
+export type ProtectionMatcher =
+  | { Name: string[] }
+  | { ExePrefix: string[] }
+  | { Pid: number[] }
+  | 'KernelThread'
+  | { User: string[] }
+  | { Ancestor: string[] };
+
+export interface ProtectionRule {
+  id: string;
+  description: string;
+  matcher: ProtectionMatcher;
+  severity: ProtectionSeverity;
+}
+
+export interface ProtectionPolicy {
+  rules: ProtectionRule[];
+}
+
+export interface ProtectionDecision {
+  pid: number;
+  action: string;
+  severity?: ProtectionSeverity;
+  matched_rules: ProtectionRule[];
+  confirmation_token?: string;
+}
+
+export interface EnvironmentVariable {
+  name: string;
+  value: string;
+  masked: boolean;
+}
+
+export type OpenFileKind = 'file' | 'socket' | 'pipe' | 'device' | 'anon_inode' | 'other';
+
+export interface OpenFile {
+  fd: number;
+  target: string;
+  kind: OpenFileKind;
+}
+
+export interface ProcessPort {
+  protocol: string;
+  local_address: string;
+  port: number;
+}
+
+export interface ProcessDetail {
+  process: DetailedProcessInfo;
+  cwd?: string;
+  environ: EnvironmentVariable[];
+  thread_count?: number;
+  open_files?: OpenFile[];
+  disk_read_bytes: number;
+  disk_written_bytes: number;
+  user_id?: string;
+  user_name?: string;
+  group_id?: string;
+  group_name?: string;
+  listening_ports: ProcessPort[];
+}
+
+export interface ResourceSample {
+  timestamp: number;
+  cpu_usage: number;
+  memory: number;
+  virtual_memory: number;
+  thread_count?: number;
+  disk_read_bytes: number;
+  disk_written_bytes: number;
+  process_count: number;
+}
+
+export interface ProcessHistoryQuery {
+  pid?: number;
+  name?: string;
+  resolution_secs?: number;
+  since?: number;
+}
+
+export interface ProcessHistorySeries {
+  pid?: number;
+  name: string;
+  resolution_secs: number;
+  samples: ResourceSample[];
+}
+
+export interface RestartOptions {
+  grace_period_ms?: number;
+  include_tree?: boolean;
+  port_release_timeout_ms?: number;
+  port_restore_timeout_ms?: number;
+}
+
+export interface RestartResult {
+  old_pid: number;
+  new_pid: number;
+  cmd: string[];
+  cwd?: string;
+  termination: TerminationResult;
+  ports: number[];
+  unreleased_ports: number[];
+  restored_ports: number[];
+  missing_ports: number[];
+  running: boolean;
+  elapsed_ms: number;
+}